@@ -1,18 +1,29 @@
 use std::sync::Arc;
 use sqlx::PgPool;
-use redis::Client as RedisClient;
 
+use crate::clients::HttpClient;
 use crate::config::Config;
-use crate::services::{IssService, OsdrService, SpaceService};
+use crate::repo::ReadCache;
+use crate::services::{
+    ApodService, AstroService, FlareService, IssService, JobService, JwstService, NeoService, OsdrService, SpaceService, SpaceWeatherService,
+};
 
 #[derive(Clone)]
 pub struct AppState {
     pub config: Arc<Config>,
     pub pool: PgPool,
-    pub redis: Option<Arc<RedisClient>>,
+    pub cache: ReadCache,
+    pub http: HttpClient,
     pub iss_service: Arc<IssService>,
     pub osdr_service: Arc<OsdrService>,
     pub space_service: Arc<SpaceService>,
+    pub space_weather_service: Arc<SpaceWeatherService>,
+    pub flare_service: Arc<FlareService>,
+    pub job_service: Arc<JobService>,
+    pub jwst_service: Arc<JwstService>,
+    pub neo_service: Arc<NeoService>,
+    pub astro_service: Arc<AstroService>,
+    pub apod_service: Arc<ApodService>,
 }
 
 
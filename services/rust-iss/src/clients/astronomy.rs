@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+
+use crate::clients::http::{upstream_error, HttpClient};
+use crate::config::AstronomyConfig;
+use crate::domain::{ApiError, AstroEvent, AstroEventsResponse};
+
+#[async_trait]
+pub trait AstronomyClientTrait: Send + Sync {
+    async fn fetch_events(
+        &self,
+        body: &str,
+        lat: f64,
+        lon: f64,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<AstroEvent>, ApiError>;
+}
+
+pub struct AstronomyClient {
+    http: HttpClient,
+    host: String,
+    app_id: String,
+    app_secret: String,
+}
+
+impl AstronomyClient {
+    pub fn new(http: HttpClient, config: &AstronomyConfig) -> Self {
+        Self {
+            http,
+            host: config.host.trim_end_matches('/').to_string(),
+            app_id: config.app_id.clone(),
+            app_secret: config.app_secret.clone(),
+        }
+    }
+
+    pub fn is_configured(&self) -> bool {
+        !self.app_id.is_empty() && !self.app_secret.is_empty()
+    }
+}
+
+#[async_trait]
+impl AstronomyClientTrait for AstronomyClient {
+    async fn fetch_events(
+        &self,
+        body: &str,
+        lat: f64,
+        lon: f64,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<AstroEvent>, ApiError> {
+        if !self.is_configured() {
+            return Err(ApiError::ServiceUnavailable(
+                "ASTRO_APP_ID/ASTRO_APP_SECRET are not configured".to_string(),
+            ));
+        }
+
+        let url = format!("{}/api/v2/bodies/events/{}", self.host, body);
+        let resp: AstroEventsResponse = self
+            .http
+            .get(&url)
+            .basic_auth(&self.app_id, &self.app_secret)
+            .query("latitude", lat)
+            .query("longitude", lon)
+            .query("elevation", 0)
+            .query("from_date", from)
+            .query("to_date", to)
+            .query("time", "00:00:00")
+            .send_json()
+            .await
+            .map_err(upstream_error("AstronomyAPI"))?;
+
+        Ok(resp.into_events())
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+use crate::config::BreakerConfig;
+use crate::clients::error::HttpError;
+use crate::domain::{CircuitState, CircuitStatus};
+
+// Circuit breaker на каждый апстрим. После failure_threshold неудач подряд цепь
+// размыкается и вызовы сразу получают ошибку; через cooldown пропускается одна
+// пробная попытка (half-open), её успех замыкает цепь, неудача — снова размыкает.
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    config: BreakerConfig,
+    circuits: Arc<Mutex<HashMap<String, Circuit>>>,
+}
+
+struct Circuit {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_until: Option<Instant>,
+    probe_started: Option<Instant>,
+    opened_total: u64,
+    rejected_total: u64,
+    last_error: Option<String>,
+    changed_at: DateTime<Utc>,
+}
+
+impl Default for Circuit {
+    fn default() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            opened_until: None,
+            probe_started: None,
+            opened_total: 0,
+            rejected_total: 0,
+            last_error: None,
+            changed_at: Utc::now(),
+        }
+    }
+}
+
+impl Circuit {
+    fn set_state(&mut self, state: CircuitState) {
+        if self.state != state {
+            self.state = state;
+            self.changed_at = Utc::now();
+        }
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(config: BreakerConfig) -> Self {
+        Self {
+            config,
+            circuits: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn cooldown(&self) -> Duration {
+        Duration::from_secs(self.config.cooldown_seconds)
+    }
+
+    pub fn check(&self, upstream: &str) -> Result<(), HttpError> {
+        let mut circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        let circuit = circuits.entry(upstream.to_string()).or_default();
+        let now = Instant::now();
+
+        match circuit.state {
+            CircuitState::Closed => Ok(()),
+            CircuitState::Open => match circuit.opened_until {
+                Some(until) if until > now => {
+                    circuit.rejected_total += 1;
+                    Err(HttpError::CircuitOpen {
+                        upstream: upstream.to_string(),
+                        retry_in_seconds: until.saturating_duration_since(now).as_secs().max(1),
+                        last_error: circuit.last_error.clone().unwrap_or_default(),
+                    })
+                }
+                _ => {
+                    circuit.set_state(CircuitState::HalfOpen);
+                    circuit.probe_started = Some(now);
+                    tracing::info!("circuit for {} is half-open, probing", upstream);
+                    Ok(())
+                }
+            },
+            // Одна пробная попытка; если её результат потерян (вызов отменён), через cooldown пускаем следующую
+            CircuitState::HalfOpen => match circuit.probe_started {
+                Some(started) if now.duration_since(started) < self.cooldown() => {
+                    circuit.rejected_total += 1;
+                    Err(HttpError::CircuitOpen {
+                        upstream: upstream.to_string(),
+                        retry_in_seconds: (self.cooldown() - now.duration_since(started)).as_secs().max(1),
+                        last_error: "half-open, probe in progress".to_string(),
+                    })
+                }
+                _ => {
+                    circuit.probe_started = Some(now);
+                    Ok(())
+                }
+            },
+        }
+    }
+
+    pub fn record_success(&self, upstream: &str) {
+        let mut circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        let circuit = circuits.entry(upstream.to_string()).or_default();
+        if circuit.state != CircuitState::Closed {
+            tracing::info!("circuit for {} closed", upstream);
+        }
+        circuit.consecutive_failures = 0;
+        circuit.opened_until = None;
+        circuit.probe_started = None;
+        circuit.set_state(CircuitState::Closed);
+    }
+
+    pub fn record_failure(&self, upstream: &str, error: &str) {
+        let mut circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        let circuit = circuits.entry(upstream.to_string()).or_default();
+        circuit.consecutive_failures += 1;
+        circuit.last_error = Some(error.to_string());
+
+        let trip = circuit.state == CircuitState::HalfOpen
+            || (circuit.state == CircuitState::Closed
+                && circuit.consecutive_failures >= self.config.failure_threshold);
+        if trip {
+            tracing::warn!(
+                "circuit for {} opened for {}s after {} failures: {}",
+                upstream,
+                self.config.cooldown_seconds,
+                circuit.consecutive_failures,
+                error
+            );
+            circuit.opened_until = Some(Instant::now() + self.cooldown());
+            circuit.probe_started = None;
+            circuit.opened_total += 1;
+            circuit.set_state(CircuitState::Open);
+        }
+    }
+
+    pub fn status(&self) -> BTreeMap<String, CircuitStatus> {
+        let circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        circuits
+            .iter()
+            .map(|(upstream, c)| {
+                let status = CircuitStatus {
+                    state: c.state,
+                    consecutive_failures: c.consecutive_failures,
+                    opened_total: c.opened_total,
+                    rejected_total: c.rejected_total,
+                    retry_in_seconds: c
+                        .opened_until
+                        .filter(|_| c.state == CircuitState::Open)
+                        .map(|t| t.saturating_duration_since(now).as_secs()),
+                    last_error: c.last_error.clone(),
+                    changed_at: c.changed_at,
+                };
+                (upstream.clone(), status)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use reqwest::header::{HeaderMap, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+
+// URL с датами в query меняются каждый день, поэтому хранилище ограничено
+const MAX_ENTRIES: usize = 1024;
+
+#[derive(Clone)]
+struct Validators {
+    etag: Option<HeaderValue>,
+    last_modified: Option<HeaderValue>,
+}
+
+// ETag / Last-Modified последнего успешного ответа по каждому URL
+#[derive(Clone, Default)]
+pub struct ValidatorStore {
+    entries: Arc<Mutex<HashMap<String, Validators>>>,
+}
+
+impl ValidatorStore {
+    pub fn apply(&self, url: &str, headers: &mut HeaderMap) {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(v) = entries.get(url) else {
+            return;
+        };
+        if let Some(etag) = &v.etag {
+            headers.insert(IF_NONE_MATCH, etag.clone());
+        }
+        if let Some(modified) = &v.last_modified {
+            headers.insert(IF_MODIFIED_SINCE, modified.clone());
+        }
+    }
+
+    pub fn record(&self, url: &str, headers: &HeaderMap) {
+        let validators = Validators {
+            etag: headers.get(ETAG).cloned(),
+            last_modified: headers.get(LAST_MODIFIED).cloned(),
+        };
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if validators.etag.is_none() && validators.last_modified.is_none() {
+            entries.remove(url);
+            return;
+        }
+        if entries.len() >= MAX_ENTRIES && !entries.contains_key(url) {
+            entries.clear();
+        }
+        entries.insert(url.to_string(), validators);
+    }
+
+    pub fn forget(&self, url_prefix: &str) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|url, _| !url.starts_with(url_prefix));
+    }
+}
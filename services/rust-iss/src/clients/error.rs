@@ -0,0 +1,82 @@
+use reqwest::StatusCode;
+use thiserror::Error;
+
+use crate::domain::ApiError;
+
+// Сколько символов тела ошибочного ответа попадает в сообщение
+const BODY_EXCERPT_CHARS: usize = 300;
+
+// Ошибки HttpClient. URL в сообщениях без query, чтобы не светить api_key.
+#[derive(Error, Debug)]
+pub enum HttpError {
+    #[error("request to {url} timed out")]
+    Timeout { url: String },
+
+    #[error("cannot connect to {url}: {message}")]
+    Connect { url: String, message: String },
+
+    #[error("{url} returned HTTP {status}: {body}")]
+    Status { url: String, status: StatusCode, body: String },
+
+    #[error("invalid response body from {url}: {message}")]
+    Decode { url: String, message: String },
+
+    #[error("rate limit for {host} exceeded, retry in {retry_in_seconds}s")]
+    RateLimited { host: String, retry_in_seconds: u64 },
+
+    #[error("circuit for {upstream} is open, retry in {retry_in_seconds}s (last error: {last_error})")]
+    CircuitOpen { upstream: String, retry_in_seconds: u64, last_error: String },
+
+    #[error("request to {url} failed: {message}")]
+    Request { url: String, message: String },
+}
+
+impl HttpError {
+    pub fn from_reqwest(url: &str, e: reqwest::Error) -> Self {
+        let url = url.to_string();
+        let (timeout, connect, decode) = (e.is_timeout(), e.is_connect(), e.is_decode());
+        let message = e.without_url().to_string();
+        if timeout {
+            HttpError::Timeout { url }
+        } else if connect {
+            HttpError::Connect { url, message }
+        } else if decode {
+            HttpError::Decode { url, message }
+        } else {
+            HttpError::Request { url, message }
+        }
+    }
+
+    pub fn status(url: &str, status: StatusCode, body: &str) -> Self {
+        let mut excerpt: String = body.trim().chars().take(BODY_EXCERPT_CHARS).collect();
+        if body.trim().chars().count() > BODY_EXCERPT_CHARS {
+            excerpt.push('…');
+        }
+        HttpError::Status {
+            url: url.to_string(),
+            status,
+            body: excerpt,
+        }
+    }
+
+    // Ошибка апстрима в терминах API сервиса; message уже содержит имя апстрима
+    pub fn into_api_error(self, message: String) -> ApiError {
+        match self {
+            HttpError::Timeout { .. } => ApiError::GatewayTimeout(message),
+            HttpError::RateLimited { .. } => ApiError::RateLimitExceeded,
+            HttpError::CircuitOpen { .. } => ApiError::ServiceUnavailable(message),
+            HttpError::Status { status, .. } if status == StatusCode::NOT_FOUND => ApiError::NotFound(message),
+            HttpError::Status { status, .. } if status == StatusCode::TOO_MANY_REQUESTS => {
+                ApiError::RateLimitExceeded
+            }
+            _ => ApiError::ExternalApi(message),
+        }
+    }
+}
+
+impl From<HttpError> for ApiError {
+    fn from(e: HttpError) -> Self {
+        let message = e.to_string();
+        e.into_api_error(message)
+    }
+}
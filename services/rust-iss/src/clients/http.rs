@@ -1,15 +1,54 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::time::Duration;
-use reqwest::Client;
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client, Method, StatusCode, Url};
 use serde_json::Value;
 
+use crate::clients::breaker::CircuitBreaker;
+use crate::clients::conditional::ValidatorStore;
+use crate::clients::error::HttpError;
+use crate::clients::rate_limit::{QuotaStatus, RateLimitStats, RateLimiter};
+use crate::clients::request::{HttpRequest, HttpResponse};
 use crate::config::{Config, RetryConfig};
+use crate::domain::{ApiError, CircuitStatus, Fetched};
 
 #[derive(Clone)]
 pub struct HttpClient {
     client: Arc<Client>,
     retry: RetryConfig,
-    user_agent: String,
+    limiter: RateLimiter,
+    breaker: CircuitBreaker,
+    validators: ValidatorStore,
+}
+
+// Ошибка HttpClient с именем API; HTTP-статус ответа сервиса зависит от вида ошибки
+pub fn upstream_error(api: &'static str) -> impl Fn(HttpError) -> ApiError {
+    move |e| {
+        let message = format!("{} API error: {}", api, e);
+        e.into_api_error(message)
+    }
+}
+
+fn host_of(url: &str) -> String {
+    Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+// Апстрим для circuit breaker — хост и первый сегмент пути, чтобы недоступность
+// DONKI не отключала APOD и NeoWs на том же api.nasa.gov
+fn upstream_of(url: &str) -> String {
+    let Ok(u) = Url::parse(url) else {
+        return String::new();
+    };
+    let host = u.host_str().unwrap_or_default();
+    match u.path_segments().and_then(|mut s| s.next()).filter(|s| !s.is_empty()) {
+        Some(segment) => format!("{}/{}", host, segment),
+        None => host.to_string(),
+    }
 }
 
 impl HttpClient {
@@ -23,107 +62,168 @@ impl HttpClient {
         Ok(Self {
             client: Arc::new(client),
             retry: config.retry.clone(),
-            user_agent: config.user_agent.clone(),
+            limiter: RateLimiter::new(config.rate_limit.clone()),
+            breaker: CircuitBreaker::new(config.breaker.clone()),
+            validators: ValidatorStore::default(),
         })
     }
 
-    pub async fn get_json(&self, url: &str) -> Result<Value, reqwest::Error> {
-        self.get_json_with_retry(url).await
+    pub fn rate_limit_stats(&self) -> BTreeMap<String, RateLimitStats> {
+        self.limiter.stats()
+    }
+
+    pub fn circuit_status(&self) -> BTreeMap<String, CircuitStatus> {
+        self.breaker.status()
+    }
+
+    pub fn quota_status(&self) -> BTreeMap<String, QuotaStatus> {
+        self.limiter.quota()
+    }
+
+    pub fn request(&self, method: Method, url: &str) -> HttpRequest<'_> {
+        HttpRequest::new(self, method, url)
+    }
+
+    pub fn get(&self, url: &str) -> HttpRequest<'_> {
+        self.request(Method::GET, url)
+    }
+
+    pub fn post(&self, url: &str) -> HttpRequest<'_> {
+        self.request(Method::POST, url)
+    }
+
+    pub async fn get_json(&self, url: &str) -> Result<Value, HttpError> {
+        self.get(url).send_json().await
     }
 
     pub async fn get_json_with_query(
         &self,
         url: &str,
         query: &[(String, String)],
-    ) -> Result<Value, reqwest::Error> {
-        // Конвертируем Vec<(String, String)> в Vec<(&str, &str)> для передачи
-        let query_refs: Vec<(&str, &str)> = query.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
-        self.get_json_with_query_retry(url, &query_refs).await
+    ) -> Result<Value, HttpError> {
+        self.get(url).query_pairs(query).send_json().await
     }
 
-    async fn get_json_with_retry(&self, url: &str) -> Result<Value, reqwest::Error> {
-        let mut last_error: Option<reqwest::Error> = None;
-        let mut delay_ms = self.retry.initial_delay_ms;
-
-        for attempt in 1..=self.retry.max_attempts {
-            match self.client.get(url).send().await {
-                Ok(resp) => {
-                    if resp.status().is_success() {
-                        return resp.json().await;
-                    } else if resp.status().is_client_error() {
-                        // Не повторяем при клиентских ошибках (4xx)
-                        return Err(resp.error_for_status().unwrap_err());
-                    }
-                    // Сохраняем ошибку статуса
-                    last_error = Some(resp.error_for_status().unwrap_err());
-                }
-                Err(e) => {
-                    last_error = Some(e);
-                    if attempt < self.retry.max_attempts {
-                        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
-                        delay_ms = (delay_ms as f64 * self.retry.backoff_multiplier) as u64;
-                        delay_ms = delay_ms.min(self.retry.max_delay_ms);
-                    }
-                }
-            }
-        }
+    // Условный запрос: с If-None-Match / If-Modified-Since от прошлого ответа по этому URL
+    pub async fn get_json_conditional(
+        &self,
+        url: &str,
+        query: &[(String, String)],
+    ) -> Result<Fetched, HttpError> {
+        self.get(url).query_pairs(query).conditional().send_fetched().await
+    }
 
-        // Возвращаем последнюю ошибку или создаем новую через запрос к невалидному URL
-        Err(last_error.unwrap_or_else(|| {
-            // Создаем ошибку через попытку запроса к невалидному URL
-            // Это будет синхронная операция, но reqwest::Error можно создать и так
-            // Используем блокирующий вызов для создания ошибки
-            match reqwest::blocking::get("http://[::1]:0") {
-                Ok(_) => unreachable!(),
-                Err(e) => e,
-            }
-        }))
+    // Следующий запрос к URL с этим префиксом будет безусловным
+    pub fn forget_validators(&self, url_prefix: &str) {
+        self.validators.forget(url_prefix);
     }
 
-    async fn get_json_with_query_retry(
-        &self,
-        url: &str,
-        query: &[(&str, &str)],
-    ) -> Result<Value, reqwest::Error> {
-        let mut last_error: Option<reqwest::Error> = None;
+    // Отправка с повторами. Возвращает 2xx или 304 (только для условного запроса),
+    // всё остальное — HttpError
+    pub(crate) async fn execute(&self, spec: &HttpRequest<'_>) -> Result<HttpResponse, HttpError> {
+        let url = spec.url.as_str();
+        let mut last_error: Option<HttpError> = None;
         let mut delay_ms = self.retry.initial_delay_ms;
+        let host = host_of(url);
+        let upstream = upstream_of(url);
+        let max_attempts = if spec.retryable() { self.retry.max_attempts } else { self.retry.max_attempts.min(1) };
+
+        for attempt in 1..=max_attempts {
+            // Разомкнутая цепь обрывает и повторы
+            self.breaker.check(&upstream)?;
+            // Каждая попытка, включая повторы, расходует бюджет хоста
+            self.limiter.acquire(&host).await?;
 
-        for attempt in 1..=self.retry.max_attempts {
-            let mut req = self.client.get(url);
-            for (k, v) in query {
-                req = req.query(&[(*k, *v)]);
+            let mut request = spec.build(&self.client)?;
+            let request_url = request.url().to_string();
+            if spec.conditional {
+                self.validators.apply(&request_url, request.headers_mut());
             }
 
-            match req.send().await {
+            let mut retry_after = None;
+            match self.client.execute(request).await {
                 Ok(resp) => {
-                    if resp.status().is_success() {
-                        return resp.json().await;
-                    } else if resp.status().is_client_error() {
-                        return Err(resp.error_for_status().unwrap_err());
+                    self.limiter.record_quota(&host, resp.headers());
+                    let status = resp.status();
+                    let headers = resp.headers().clone();
+
+                    if status.is_success() || (status == StatusCode::NOT_MODIFIED && spec.conditional) {
+                        self.breaker.record_success(&upstream);
+                        let body = resp.bytes().await.map_err(|e| HttpError::from_reqwest(url, e))?;
+                        if spec.conditional && status.is_success() {
+                            self.validators.record(&request_url, &headers);
+                        }
+                        return Ok(HttpResponse {
+                            status,
+                            headers,
+                            body: body.to_vec(),
+                            url: url.to_string(),
+                        });
                     }
-                    // Сохраняем ошибку статуса
-                    last_error = Some(resp.error_for_status().unwrap_err());
+
+                    // 5xx и сетевые ошибки — признак недоступности апстрима, 4xx — нет
+                    if status.is_server_error() {
+                        self.breaker.record_failure(&upstream, &format!("HTTP {}", status));
+                    } else if status != StatusCode::TOO_MANY_REQUESTS {
+                        self.breaker.record_success(&upstream);
+                    }
+
+                    let rate_limited = status == StatusCode::TOO_MANY_REQUESTS;
+                    if rate_limited || status == StatusCode::SERVICE_UNAVAILABLE {
+                        retry_after = parse_retry_after(&headers);
+                    }
+                    let body = resp.text().await.unwrap_or_default();
+                    let error = HttpError::status(url, status, &body);
+
+                    if let Some(delay) = retry_after {
+                        self.limiter.block(&host, delay);
+                        // Ждать дольше допустимого не будем — остальные вызовы тоже получат отказ сразу
+                        if delay.as_secs() > self.retry.retry_after_max_seconds {
+                            tracing::warn!("{} asked to retry after {:?}, giving up", host, delay);
+                            return Err(if rate_limited {
+                                HttpError::RateLimited {
+                                    host,
+                                    retry_in_seconds: delay.as_secs(),
+                                }
+                            } else {
+                                error
+                            });
+                        }
+                    } else if !rate_limited && !status.is_server_error() {
+                        // Не повторяем при остальных клиентских ошибках (4xx) и неожиданных 3xx
+                        return Err(error);
+                    }
+                    last_error = Some(error);
                 }
                 Err(e) => {
-                    last_error = Some(e);
-                    if attempt < self.retry.max_attempts {
-                        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
-                        delay_ms = (delay_ms as f64 * self.retry.backoff_multiplier) as u64;
-                        delay_ms = delay_ms.min(self.retry.max_delay_ms);
-                    }
+                    let error = HttpError::from_reqwest(url, e);
+                    self.breaker.record_failure(&upstream, &error.to_string());
+                    last_error = Some(error);
                 }
             }
-        }
 
-        // Возвращаем последнюю ошибку или создаем новую
-        Err(last_error.unwrap_or_else(|| {
-            // Создаем ошибку через попытку запроса к невалидному URL
-            match reqwest::blocking::get("http://[::1]:0") {
-                Ok(_) => unreachable!(),
-                Err(e) => e,
+            if attempt < max_attempts {
+                // Retry-After важнее собственного backoff
+                let delay = retry_after.unwrap_or(Duration::from_millis(delay_ms));
+                tokio::time::sleep(delay).await;
+                delay_ms = (delay_ms as f64 * self.retry.backoff_multiplier) as u64;
+                delay_ms = delay_ms.min(self.retry.max_delay_ms);
             }
+        }
+
+        Err(last_error.unwrap_or_else(|| HttpError::Request {
+            url: url.to_string(),
+            message: "no attempts made (RETRY_MAX_ATTEMPTS=0)".to_string(),
         }))
     }
 }
 
-
+// Retry-After: число секунд или HTTP-дата
+fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+    Some((at - Utc::now()).to_std().unwrap_or(Duration::ZERO))
+}
@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use serde_json::Value;
 
-use crate::clients::http::HttpClient;
+use crate::clients::http::{upstream_error, HttpClient};
 use crate::domain::ApiError;
 
 #[async_trait]
@@ -25,7 +25,7 @@ impl IssClientTrait for IssClient {
         self.http
             .get_json(url)
             .await
-            .map_err(|e| ApiError::ExternalApi(format!("ISS API error: {}", e)))
+            .map_err(upstream_error("ISS"))
     }
 }
 
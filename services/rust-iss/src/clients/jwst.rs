@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::clients::http::{upstream_error, HttpClient};
+use crate::config::JwstConfig;
+use crate::domain::{ApiError, JwstFilter, JwstObservation};
+
+#[async_trait]
+pub trait JwstClientTrait: Send + Sync {
+    async fn fetch_observations(
+        &self,
+        filter: &JwstFilter,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Vec<JwstObservation>, ApiError>;
+}
+
+pub struct JwstClient {
+    http: HttpClient,
+    host: String,
+    api_key: String,
+    email: Option<String>,
+}
+
+impl JwstClient {
+    pub fn new(http: HttpClient, config: &JwstConfig) -> Self {
+        Self {
+            http,
+            host: config.host.trim_end_matches('/').to_string(),
+            api_key: config.api_key.clone(),
+            email: config.email.clone(),
+        }
+    }
+
+    pub fn is_configured(&self) -> bool {
+        !self.api_key.is_empty()
+    }
+
+    // У API нет фильтра по инструменту, а program и suffix — разные эндпоинты:
+    // выбираем самый узкий, остальное фильтруется на нашей стороне
+    fn path(filter: &JwstFilter) -> String {
+        if let Some(program) = &filter.program {
+            format!("program/id/{}", program)
+        } else if let Some(suffix) = &filter.suffix {
+            format!("all/suffix/{}", suffix.trim_start_matches('/'))
+        } else {
+            "all/type/jpg".to_string()
+        }
+    }
+}
+
+#[async_trait]
+impl JwstClientTrait for JwstClient {
+    async fn fetch_observations(
+        &self,
+        filter: &JwstFilter,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Vec<JwstObservation>, ApiError> {
+        if !self.is_configured() {
+            return Err(ApiError::ServiceUnavailable("JWST_API_KEY is not configured".to_string()));
+        }
+
+        let url = format!("{}/{}", self.host, Self::path(filter));
+        let mut req = self
+            .http
+            .get(&url)
+            .header("x-api-key", &self.api_key)
+            .query("page", page)
+            .query("perPage", per_page);
+        if let Some(email) = &self.email {
+            req = req.header("email", email);
+        }
+
+        let json: Value = req.send_json().await.map_err(upstream_error("JWST"))?;
+        let list = json
+            .get("body")
+            .or_else(|| json.get("data"))
+            .cloned()
+            .unwrap_or(json);
+        let items = list.as_array().cloned().unwrap_or_default();
+
+        // Битые элементы пропускаем, чтобы один не ронял всю страницу
+        let observations = items
+            .into_iter()
+            .filter_map(|v| match serde_json::from_value::<JwstObservation>(v) {
+                Ok(o) => Some(o),
+                Err(e) => {
+                    tracing::warn!("JWST item skipped: {}", e);
+                    None
+                }
+            })
+            .filter(|o| match (&filter.program, &filter.suffix) {
+                (Some(_), Some(suffix)) => o.details.suffix.as_deref() == Some(suffix.as_str()),
+                _ => true,
+            })
+            .filter(|o| match &filter.instrument {
+                Some(inst) => o.instruments().iter().any(|i| i.eq_ignore_ascii_case(inst)),
+                None => true,
+            })
+            .collect();
+
+        Ok(observations)
+    }
+}
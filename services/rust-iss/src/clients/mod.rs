@@ -1,10 +1,21 @@
+pub mod astronomy;
+pub mod breaker;
+pub mod conditional;
+pub mod error;
 pub mod http;
 pub mod iss;
+pub mod jwst;
 pub mod nasa;
+pub mod rate_limit;
+pub mod request;
 pub mod spacex;
 
+pub use astronomy::AstronomyClient;
+pub use error::HttpError;
 pub use http::HttpClient;
+pub use request::{Auth, HttpRequest, HttpResponse};
 pub use iss::IssClient;
+pub use jwst::JwstClient;
 pub use nasa::NasaClient;
 pub use spacex::SpaceXClient;
 
@@ -1,17 +1,42 @@
 use async_trait::async_trait;
-use chrono::{Days, Utc};
+use chrono::{Days, NaiveDate, Utc};
+use reqwest::StatusCode;
 use serde_json::Value;
 
-use crate::clients::http::HttpClient;
-use crate::domain::ApiError;
+use crate::clients::http::{upstream_error, HttpClient};
+use crate::domain::{ApiError, Fetched};
+
 
 #[async_trait]
 pub trait NasaClientTrait: Send + Sync {
-    async fn fetch_apod(&self, api_key: &str) -> Result<Value, ApiError>;
-    async fn fetch_neo_feed(&self, api_key: &str, days: u64) -> Result<Value, ApiError>;
-    async fn fetch_donki_flr(&self, api_key: &str, days: u64) -> Result<Value, ApiError>;
-    async fn fetch_donki_cme(&self, api_key: &str, days: u64) -> Result<Value, ApiError>;
+    async fn fetch_apod(&self, api_key: &str) -> Result<Fetched, ApiError>;
+    async fn fetch_apod_date(&self, api_key: &str, date: NaiveDate) -> Result<Value, ApiError>;
+    async fn fetch_apod_range(&self, api_key: &str, start: NaiveDate, end: NaiveDate) -> Result<Value, ApiError>;
+    async fn fetch_neo_feed(&self, api_key: &str, start: NaiveDate, end: NaiveDate) -> Result<Fetched, ApiError>;
+    async fn fetch_donki_flr(&self, api_key: &str, start: NaiveDate, end: NaiveDate) -> Result<Fetched, ApiError>;
+    async fn fetch_donki_flr_range(&self, api_key: &str, start: NaiveDate, end: NaiveDate) -> Result<Value, ApiError>;
+    async fn fetch_donki_cme(&self, api_key: &str, days: u64) -> Result<Fetched, ApiError>;
+    async fn fetch_donki_gst(&self, api_key: &str, days: u64) -> Result<Fetched, ApiError>;
+    async fn fetch_donki_sep(&self, api_key: &str, days: u64) -> Result<Fetched, ApiError>;
+    async fn fetch_donki_ips(&self, api_key: &str, days: u64) -> Result<Fetched, ApiError>;
+    async fn fetch_donki_hss(&self, api_key: &str, days: u64) -> Result<Fetched, ApiError>;
+    async fn fetch_neo_lookup(&self, api_key: &str, neo_id: &str) -> Result<Value, ApiError>;
     async fn fetch_osdr(&self, url: &str, api_key: &str) -> Result<Value, ApiError>;
+    fn forget_validators(&self, source: &str);
+    fn quota_low(&self) -> bool;
+}
+
+// Адрес ленты источника space_cache — по нему сбрасываются валидаторы только этого источника
+fn source_url(source: &str) -> String {
+    match source {
+        "apod" => "https://api.nasa.gov/planetary/apod".to_string(),
+        "neo" => "https://api.nasa.gov/neo/rest/v1/feed".to_string(),
+        donki => format!("https://api.nasa.gov/DONKI/{}", donki.to_uppercase()),
+    }
+}
+
+fn last_days(days: u64) -> NaiveDate {
+    Utc::now().date_naive() - Days::new(days)
 }
 
 #[derive(Clone)]
@@ -23,74 +48,147 @@ impl NasaClient {
     pub fn new(http: HttpClient) -> Self {
         Self { http }
     }
+
+    // Все ленты DONKI принимают одинаковое окно startDate..endDate.
+    // Без conditional — для разовых запросов истории: 304 там нечем подтвердить
+    async fn fetch_donki(
+        &self,
+        kind: &str,
+        api: &'static str,
+        api_key: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+        conditional: bool,
+    ) -> Result<Fetched, ApiError> {
+        let mut query = vec![
+            ("startDate".to_string(), start.to_string()),
+            ("endDate".to_string(), end.to_string()),
+        ];
+        if !api_key.is_empty() {
+            query.push(("api_key".to_string(), api_key.to_string()));
+        }
+
+        let url = format!("https://api.nasa.gov/DONKI/{}", kind);
+        let mut request = self.http.get(&url).query_pairs(&query);
+        if conditional {
+            request = request.conditional();
+        }
+        let resp = request.send().await.map_err(upstream_error(api))?;
+        if resp.status == StatusCode::NOT_MODIFIED {
+            return Ok(Fetched::Unchanged);
+        }
+
+        // Пустой период DONKI отдаёт пустым телом вместо []
+        if resp.body.iter().all(|b| b.is_ascii_whitespace()) {
+            return Ok(Fetched::Fresh(Value::Array(Vec::new())));
+        }
+        resp.json().map(Fetched::Fresh).map_err(|e| {
+            self.http.forget_validators(&url);
+            upstream_error(api)(e)
+        })
+    }
 }
 
 #[async_trait]
 impl NasaClientTrait for NasaClient {
-    async fn fetch_apod(&self, api_key: &str) -> Result<Value, ApiError> {
+    async fn fetch_apod(&self, api_key: &str) -> Result<Fetched, ApiError> {
         let mut query = vec![("thumbs".to_string(), "true".to_string())];
         if !api_key.is_empty() {
             query.push(("api_key".to_string(), api_key.to_string()));
         }
 
         self.http
-            .get_json_with_query("https://api.nasa.gov/planetary/apod", &query)
+            .get_json_conditional("https://api.nasa.gov/planetary/apod", &query)
             .await
-            .map_err(|e| ApiError::ExternalApi(format!("APOD API error: {}", e)))
+            .map_err(upstream_error("APOD"))
+    }
+
+    async fn fetch_apod_date(&self, api_key: &str, date: NaiveDate) -> Result<Value, ApiError> {
+        let mut request = self
+            .http
+            .get("https://api.nasa.gov/planetary/apod")
+            .query("date", date)
+            .query("thumbs", "true");
+        if !api_key.is_empty() {
+            request = request.query("api_key", api_key);
+        }
+
+        request.send_json().await.map_err(upstream_error("APOD"))
     }
 
-    async fn fetch_neo_feed(&self, api_key: &str, days: u64) -> Result<Value, ApiError> {
-        let today = Utc::now().date_naive();
-        let start = today - Days::new(days);
+    // Диапазон отдаётся массивом, по записи на день
+    async fn fetch_apod_range(&self, api_key: &str, start: NaiveDate, end: NaiveDate) -> Result<Value, ApiError> {
+        let mut request = self
+            .http
+            .get("https://api.nasa.gov/planetary/apod")
+            .query("start_date", start)
+            .query("end_date", end)
+            .query("thumbs", "true");
+        if !api_key.is_empty() {
+            request = request.query("api_key", api_key);
+        }
+
+        request.send_json().await.map_err(upstream_error("APOD"))
+    }
 
+    // NeoWs принимает окно не длиннее 7 дней
+    async fn fetch_neo_feed(&self, api_key: &str, start: NaiveDate, end: NaiveDate) -> Result<Fetched, ApiError> {
         let mut query = vec![
             ("start_date".to_string(), start.to_string()),
-            ("end_date".to_string(), today.to_string()),
+            ("end_date".to_string(), end.to_string()),
         ];
         if !api_key.is_empty() {
             query.push(("api_key".to_string(), api_key.to_string()));
         }
 
         self.http
-            .get_json_with_query("https://api.nasa.gov/neo/rest/v1/feed", &query)
+            .get_json_conditional("https://api.nasa.gov/neo/rest/v1/feed", &query)
             .await
-            .map_err(|e| ApiError::ExternalApi(format!("NeoWs API error: {}", e)))
+            .map_err(upstream_error("NeoWs"))
     }
 
-    async fn fetch_donki_flr(&self, api_key: &str, days: u64) -> Result<Value, ApiError> {
-        let today = Utc::now().date_naive();
-        let start = today - Days::new(days);
+    async fn fetch_donki_flr(&self, api_key: &str, start: NaiveDate, end: NaiveDate) -> Result<Fetched, ApiError> {
+        self.fetch_donki("FLR", "DONKI FLR", api_key, start, end, true).await
+    }
 
-        let mut query = vec![
-            ("startDate".to_string(), start.to_string()),
-            ("endDate".to_string(), today.to_string()),
-        ];
-        if !api_key.is_empty() {
-            query.push(("api_key".to_string(), api_key.to_string()));
+    // Догрузка истории: окно запрашивается целиком, без валидаторов
+    async fn fetch_donki_flr_range(&self, api_key: &str, start: NaiveDate, end: NaiveDate) -> Result<Value, ApiError> {
+        match self.fetch_donki("FLR", "DONKI FLR", api_key, start, end, false).await? {
+            Fetched::Fresh(payload) => Ok(payload),
+            Fetched::Unchanged => Err(ApiError::ExternalApi(
+                "DONKI FLR answered 304 to an unconditional request".to_string(),
+            )),
         }
+    }
 
-        self.http
-            .get_json_with_query("https://api.nasa.gov/DONKI/FLR", &query)
-            .await
-            .map_err(|e| ApiError::ExternalApi(format!("DONKI FLR API error: {}", e)))
+    async fn fetch_donki_cme(&self, api_key: &str, days: u64) -> Result<Fetched, ApiError> {
+        self.fetch_donki("CME", "DONKI CME", api_key, last_days(days), Utc::now().date_naive(), true).await
     }
 
-    async fn fetch_donki_cme(&self, api_key: &str, days: u64) -> Result<Value, ApiError> {
-        let today = Utc::now().date_naive();
-        let start = today - Days::new(days);
+    async fn fetch_donki_gst(&self, api_key: &str, days: u64) -> Result<Fetched, ApiError> {
+        self.fetch_donki("GST", "DONKI GST", api_key, last_days(days), Utc::now().date_naive(), true).await
+    }
 
-        let mut query = vec![
-            ("startDate".to_string(), start.to_string()),
-            ("endDate".to_string(), today.to_string()),
-        ];
+    async fn fetch_donki_sep(&self, api_key: &str, days: u64) -> Result<Fetched, ApiError> {
+        self.fetch_donki("SEP", "DONKI SEP", api_key, last_days(days), Utc::now().date_naive(), true).await
+    }
+
+    async fn fetch_donki_ips(&self, api_key: &str, days: u64) -> Result<Fetched, ApiError> {
+        self.fetch_donki("IPS", "DONKI IPS", api_key, last_days(days), Utc::now().date_naive(), true).await
+    }
+
+    async fn fetch_donki_hss(&self, api_key: &str, days: u64) -> Result<Fetched, ApiError> {
+        self.fetch_donki("HSS", "DONKI HSS", api_key, last_days(days), Utc::now().date_naive(), true).await
+    }
+
+    // Без валидаторов: карточки объектов кэшируются в neo_objects со своим сроком свежести
+    async fn fetch_neo_lookup(&self, api_key: &str, neo_id: &str) -> Result<Value, ApiError> {
+        let mut request = self.http.get(&format!("https://api.nasa.gov/neo/rest/v1/neo/{}", neo_id));
         if !api_key.is_empty() {
-            query.push(("api_key".to_string(), api_key.to_string()));
+            request = request.query("api_key", api_key);
         }
 
-        self.http
-            .get_json_with_query("https://api.nasa.gov/DONKI/CME", &query)
-            .await
-            .map_err(|e| ApiError::ExternalApi(format!("DONKI CME API error: {}", e)))
+        request.send_json().await.map_err(upstream_error("NeoWs lookup"))
     }
 
     async fn fetch_osdr(&self, url: &str, api_key: &str) -> Result<Value, ApiError> {
@@ -103,7 +201,20 @@ impl NasaClientTrait for NasaClient {
         self.http
             .get_json_with_query(url, &query)
             .await
-            .map_err(|e| ApiError::ExternalApi(format!("OSDR API error: {}", e)))
+            .map_err(upstream_error("OSDR"))
+    }
+
+    // Ключ — URL с query, поэтому «?» отделяет FLR от любых FLR-подобных путей
+    fn forget_validators(&self, source: &str) {
+        self.http.forget_validators(&format!("{}?", source_url(source)));
+    }
+
+    // Остаток квоты api.nasa.gov ниже порога RATE_LIMIT_QUOTA_LOW_PERCENT или апстрим попросил подождать
+    fn quota_low(&self) -> bool {
+        self.http
+            .quota_status()
+            .get("api.nasa.gov")
+            .is_some_and(|q| q.low || q.blocked_for_seconds > 0)
     }
 }
 
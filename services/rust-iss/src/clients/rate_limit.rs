@@ -0,0 +1,225 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+
+use crate::config::{HostRateLimit, RateLimitConfig};
+use crate::clients::error::HttpError;
+
+// Token bucket на каждый upstream-хост. Нехватка токена ставит вызов в очередь
+// (токен резервируется заранее), если ждать дольше max_wait — сразу RateLimitExceeded.
+// Дополнительно учитывается квота из заголовков апстрима и Retry-After.
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: Arc<RateLimitConfig>,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+struct Bucket {
+    limit: HostRateLimit,
+    tokens: f64,
+    updated: Instant,
+    stats: RateLimitStats,
+    quota: Option<Quota>,
+    blocked_until: Option<Instant>,
+}
+
+// Последние значения X-RateLimit-* от апстрима
+#[derive(Debug, Clone, Copy)]
+struct Quota {
+    limit: Option<u32>,
+    remaining: u32,
+    observed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaStatus {
+    pub limit: Option<u32>,
+    pub remaining: Option<u32>,
+    pub observed_at: Option<DateTime<Utc>>,
+    pub low: bool,
+    pub blocked_for_seconds: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RateLimitStats {
+    pub requests_per_minute: u32,
+    pub burst_size: u32,
+    pub tokens: f64,
+    pub allowed: u64,
+    pub delayed: u64,
+    pub rejected: u64,
+    pub wait_ms_total: u64,
+    pub slowed: u64,
+    pub retry_after: u64,
+}
+
+impl Bucket {
+    fn new(limit: HostRateLimit) -> Self {
+        Self {
+            tokens: limit.burst_size.max(1) as f64,
+            updated: Instant::now(),
+            stats: RateLimitStats {
+                requests_per_minute: limit.requests_per_minute,
+                burst_size: limit.burst_size,
+                ..Default::default()
+            },
+            limit,
+            quota: None,
+            blocked_until: None,
+        }
+    }
+
+    fn blocked_for(&self, now: Instant) -> Duration {
+        self.blocked_until
+            .map(|t| t.saturating_duration_since(now))
+            .unwrap_or(Duration::ZERO)
+    }
+
+    // Квота считается низкой, когда остаток меньше low_percent от лимита
+    // (или от burst, если апстрим не сообщает лимит)
+    fn quota_threshold(&self, low_percent: u32) -> Option<f64> {
+        let quota = self.quota?;
+        let limit = quota.limit.unwrap_or(self.limit.burst_size.max(1) * 10);
+        let threshold = (limit as f64 * low_percent as f64 / 100.0).max(1.0);
+        (f64::from(quota.remaining) < threshold).then_some(threshold)
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let per_sec = self.limit.requests_per_minute.max(1) as f64 / 60.0;
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_sec).min(self.limit.burst_size.max(1) as f64);
+        self.updated = now;
+    }
+
+    // Ожидание до появления токена при текущем балансе (баланс может быть отрицательным — очередь)
+    fn wait_for_token(&self) -> Duration {
+        if self.tokens >= 1.0 {
+            return Duration::ZERO;
+        }
+        let per_sec = self.limit.requests_per_minute.max(1) as f64 / 60.0;
+        Duration::from_secs_f64((1.0 - self.tokens) / per_sec)
+    }
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub async fn acquire(&self, host: &str) -> Result<(), HttpError> {
+        let wait = {
+            let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+            let bucket = buckets
+                .entry(host.to_string())
+                .or_insert_with(|| Bucket::new(self.config.for_host(host)));
+            let now = Instant::now();
+            bucket.refill(now);
+
+            // Retry-After от апстрима действует на всех вызывающих этого хоста
+            let wait = bucket.wait_for_token().max(bucket.blocked_for(now));
+            if wait > Duration::from_millis(self.config.max_wait_ms) {
+                bucket.stats.rejected += 1;
+                tracing::warn!("outbound rate limit for {} exceeded, next slot in {:?}", host, wait);
+                return Err(HttpError::RateLimited {
+                    host: host.to_string(),
+                    retry_in_seconds: wait.as_secs().max(1),
+                });
+            }
+
+            bucket.tokens -= 1.0;
+            bucket.stats.allowed += 1;
+
+            // При заканчивающейся квоте замедляемся заранее, тем сильнее, чем меньше остаток
+            let mut wait = wait;
+            if let (Some(threshold), Some(quota)) =
+                (bucket.quota_threshold(self.config.quota_low_percent), bucket.quota)
+            {
+                let share = 1.0 - f64::from(quota.remaining) / threshold;
+                wait += Duration::from_millis((self.config.quota_slowdown_ms as f64 * share.max(0.1)) as u64);
+                bucket.stats.slowed += 1;
+            }
+
+            if !wait.is_zero() {
+                bucket.stats.delayed += 1;
+                bucket.stats.wait_ms_total += wait.as_millis() as u64;
+            }
+            wait
+        };
+
+        if !wait.is_zero() {
+            tracing::debug!("outbound request to {} delayed by {:?}", host, wait);
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+
+    pub fn record_quota(&self, host: &str, headers: &HeaderMap) {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u32>().ok())
+        };
+        let Some(remaining) = header("x-ratelimit-remaining") else {
+            return;
+        };
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(bucket) = buckets.get_mut(host) {
+            bucket.quota = Some(Quota {
+                limit: header("x-ratelimit-limit"),
+                remaining,
+                observed_at: Utc::now(),
+            });
+        }
+    }
+
+    // Запрещает запросы к хосту на время, указанное апстримом в Retry-After
+    pub fn block(&self, host: &str, delay: Duration) {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(bucket) = buckets.get_mut(host) {
+            let until = Instant::now() + delay;
+            bucket.blocked_until = Some(bucket.blocked_until.map_or(until, |t| t.max(until)));
+            bucket.stats.retry_after += 1;
+        }
+    }
+
+    pub fn quota(&self) -> BTreeMap<String, QuotaStatus> {
+        let buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        buckets
+            .iter()
+            .map(|(host, bucket)| {
+                let status = QuotaStatus {
+                    limit: bucket.quota.and_then(|q| q.limit),
+                    remaining: bucket.quota.map(|q| q.remaining),
+                    observed_at: bucket.quota.map(|q| q.observed_at),
+                    low: bucket.quota_threshold(self.config.quota_low_percent).is_some(),
+                    blocked_for_seconds: bucket.blocked_for(now).as_secs(),
+                };
+                (host.clone(), status)
+            })
+            .collect()
+    }
+
+    pub fn stats(&self) -> BTreeMap<String, RateLimitStats> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        buckets
+            .iter_mut()
+            .map(|(host, bucket)| {
+                bucket.refill(now);
+                let mut stats = bucket.stats.clone();
+                stats.tokens = (bucket.tokens * 100.0).round() / 100.0;
+                (host.clone(), stats)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,240 @@
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE};
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::clients::error::HttpError;
+use crate::clients::http::HttpClient;
+use crate::domain::Fetched;
+
+#[derive(Clone, Debug)]
+pub enum Auth {
+    Basic { username: String, password: String },
+    Bearer(String),
+    // Ключ в заголовке, например X-API-KEY
+    ApiKeyHeader { name: String, key: String },
+    // Ключ в query, как api_key у api.nasa.gov
+    ApiKeyQuery { name: String, key: String },
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Body {
+    Json(Value),
+    Form(Vec<(String, String)>),
+    Raw { bytes: Vec<u8>, content_type: String },
+}
+
+// Запрос через HttpClient: retry, таймауты, rate limit, circuit breaker и условные
+// запросы работают одинаково для любого метода. Собирается заново на каждую попытку.
+#[derive(Clone)]
+pub struct HttpRequest<'a> {
+    pub(crate) http: &'a HttpClient,
+    pub(crate) method: Method,
+    pub(crate) url: String,
+    pub(crate) query: Vec<(String, String)>,
+    pub(crate) headers: HeaderMap,
+    pub(crate) auth: Option<Auth>,
+    pub(crate) body: Option<Body>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) conditional: bool,
+    pub(crate) retry_unsafe: bool,
+    pub(crate) header_error: Option<String>,
+}
+
+impl<'a> HttpRequest<'a> {
+    pub(crate) fn new(http: &'a HttpClient, method: Method, url: &str) -> Self {
+        Self {
+            http,
+            method,
+            url: url.to_string(),
+            query: Vec::new(),
+            headers: HeaderMap::new(),
+            auth: None,
+            body: None,
+            timeout: None,
+            conditional: false,
+            retry_unsafe: false,
+            header_error: None,
+        }
+    }
+
+    pub fn query(mut self, name: &str, value: impl ToString) -> Self {
+        self.query.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn query_pairs(mut self, pairs: &[(String, String)]) -> Self {
+        self.query.extend_from_slice(pairs);
+        self
+    }
+
+    // Некорректное имя или значение заголовка превращается в ошибку при отправке
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        match (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(value)) {
+            (Ok(name), Ok(value)) => {
+                self.headers.insert(name, value);
+            }
+            _ => self.header_error = Some(format!("invalid header {}", name)),
+        }
+        self
+    }
+
+    pub fn accept(self, mime: &str) -> Self {
+        self.header(ACCEPT.as_str(), mime)
+    }
+
+    pub fn auth(mut self, auth: Auth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn basic_auth(self, username: &str, password: &str) -> Self {
+        self.auth(Auth::Basic {
+            username: username.to_string(),
+            password: password.to_string(),
+        })
+    }
+
+    pub fn bearer(self, token: &str) -> Self {
+        self.auth(Auth::Bearer(token.to_string()))
+    }
+
+    pub fn json<T: Serialize>(mut self, body: &T) -> Self {
+        match serde_json::to_value(body) {
+            Ok(v) => self.body = Some(Body::Json(v)),
+            Err(e) => self.header_error = Some(format!("cannot serialize body: {}", e)),
+        }
+        self
+    }
+
+    pub fn form(mut self, pairs: &[(String, String)]) -> Self {
+        self.body = Some(Body::Form(pairs.to_vec()));
+        self
+    }
+
+    pub fn body(mut self, bytes: Vec<u8>, content_type: &str) -> Self {
+        self.body = Some(Body::Raw {
+            bytes,
+            content_type: content_type.to_string(),
+        });
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    // If-None-Match / If-Modified-Since от прошлого успешного ответа по этому URL
+    pub fn conditional(mut self) -> Self {
+        self.conditional = true;
+        self
+    }
+
+    // POST и PATCH по умолчанию не повторяются: апстрим мог уже выполнить запрос
+    pub fn retry_unsafe(mut self) -> Self {
+        self.retry_unsafe = true;
+        self
+    }
+
+    pub(crate) fn retryable(&self) -> bool {
+        self.retry_unsafe || !matches!(self.method, Method::POST | Method::PATCH)
+    }
+
+    // Сборка reqwest-запроса для одной попытки
+    pub(crate) fn build(&self, client: &reqwest::Client) -> Result<reqwest::Request, HttpError> {
+        if let Some(message) = &self.header_error {
+            return Err(HttpError::Request {
+                url: self.url.clone(),
+                message: message.clone(),
+            });
+        }
+
+        let mut req = client.request(self.method.clone(), &self.url);
+        if !self.query.is_empty() {
+            req = req.query(&self.query);
+        }
+        req = req.headers(self.headers.clone());
+        if let Some(timeout) = self.timeout {
+            req = req.timeout(timeout);
+        }
+
+        req = match &self.auth {
+            Some(Auth::Basic { username, password }) => req.basic_auth(username, Some(password)),
+            Some(Auth::Bearer(token)) => req.bearer_auth(token),
+            Some(Auth::ApiKeyHeader { name, key }) => req.header(name.as_str(), key.as_str()),
+            Some(Auth::ApiKeyQuery { name, key }) => req.query(&[(name, key)]),
+            None => req,
+        };
+
+        req = match &self.body {
+            Some(Body::Json(v)) => req.json(v),
+            Some(Body::Form(pairs)) => req.form(pairs),
+            Some(Body::Raw { bytes, content_type }) => {
+                req.header(CONTENT_TYPE, content_type.as_str()).body(bytes.clone())
+            }
+            None => req,
+        };
+
+        req.build().map_err(|e| HttpError::from_reqwest(&self.url, e))
+    }
+
+    pub async fn send(self) -> Result<HttpResponse, HttpError> {
+        self.http.execute(&self).await
+    }
+
+    pub async fn send_json<T: DeserializeOwned>(self) -> Result<T, HttpError> {
+        self.send().await?.json()
+    }
+
+    // Для условного запроса: Unchanged, если апстрим ответил 304
+    pub async fn send_fetched(self) -> Result<Fetched, HttpError> {
+        let url = self.url.clone();
+        let http = self.http;
+        let resp = self.send().await?;
+        if resp.status == StatusCode::NOT_MODIFIED {
+            return Ok(Fetched::Unchanged);
+        }
+        match resp.json() {
+            Ok(v) => Ok(Fetched::Fresh(v)),
+            Err(e) => {
+                // Валидаторы для неразобранного тела не должны подтверждать старую копию
+                http.forget_validators(&url);
+                Err(e)
+            }
+        }
+    }
+}
+
+// Ответ с телом целиком в памяти — JSON, текст или бинарные данные
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+    pub(crate) url: String,
+}
+
+impl HttpResponse {
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, HttpError> {
+        serde_json::from_slice(&self.body).map_err(|e| HttpError::Decode {
+            url: self.url.clone(),
+            message: e.to_string(),
+        })
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.body
+    }
+
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok())
+    }
+}
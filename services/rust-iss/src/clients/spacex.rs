@@ -1,12 +1,14 @@
 use async_trait::async_trait;
-use serde_json::Value;
 
-use crate::clients::http::HttpClient;
-use crate::domain::ApiError;
+use crate::clients::http::{upstream_error, HttpClient};
+use crate::domain::{ApiError, Fetched};
+
+const SPACEX_API: &str = "https://api.spacexdata.com/";
 
 #[async_trait]
 pub trait SpaceXClientTrait: Send + Sync {
-    async fn fetch_next_launch(&self) -> Result<Value, ApiError>;
+    async fn fetch_next_launch(&self) -> Result<Fetched, ApiError>;
+    fn forget_validators(&self);
 }
 
 pub struct SpaceXClient {
@@ -21,11 +23,15 @@ impl SpaceXClient {
 
 #[async_trait]
 impl SpaceXClientTrait for SpaceXClient {
-    async fn fetch_next_launch(&self) -> Result<Value, ApiError> {
+    async fn fetch_next_launch(&self) -> Result<Fetched, ApiError> {
         self.http
-            .get_json("https://api.spacexdata.com/v4/launches/next")
+            .get_json_conditional("https://api.spacexdata.com/v4/launches/next", &[])
             .await
-            .map_err(|e| ApiError::ExternalApi(format!("SpaceX API error: {}", e)))
+            .map_err(upstream_error("SpaceX"))
+    }
+
+    fn forget_validators(&self) {
+        self.http.forget_validators(SPACEX_API);
     }
 }
 
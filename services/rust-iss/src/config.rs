@@ -1,8 +1,12 @@
+use std::collections::BTreeMap;
 use std::time::Duration;
 
+use chrono::NaiveDate;
+
 #[derive(Clone, Debug)]
 pub struct Config {
     pub database_url: String,
+    pub db_max_connections: u32,
     pub redis_url: String,
     pub nasa_url: String,
     pub nasa_key: String,
@@ -12,6 +16,18 @@ pub struct Config {
     pub timeouts: Timeouts,
     pub retry: RetryConfig,
     pub rate_limit: RateLimitConfig,
+    pub breaker: BreakerConfig,
+    pub jobs: JobQueueConfig,
+    pub shutdown_grace_seconds: u64,
+    pub refresh_concurrency: usize,
+    pub retention: CacheRetention,
+    pub freshness: BTreeMap<String, u64>,
+    pub read_cache: ReadCacheConfig,
+    pub manual_refresh_min_interval: u64,
+    pub jwst: JwstConfig,
+    pub astronomy: AstronomyConfig,
+    pub backfill: BackfillConfig,
+    pub neo: NeoConfig,
 }
 
 #[derive(Clone, Debug)]
@@ -22,6 +38,47 @@ pub struct FetchIntervals {
     pub neo: u64,
     pub donki: u64,
     pub spacex: u64,
+    pub jwst: u64,
+    pub backfill: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct JwstConfig {
+    pub host: String,
+    pub api_key: String,
+    pub email: Option<String>,
+    pub program_id: Option<String>,
+    pub sync_pages: u32,
+    pub per_page: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct AstronomyConfig {
+    pub host: String,
+    pub app_id: String,
+    pub app_secret: String,
+    pub bodies: Vec<String>,
+    pub events_ttl: Duration,
+}
+
+// Догрузка истории идёт окнами по chunk_days, не больше chunks_per_run окон за запуск,
+// чтобы не съедать дневную квоту api.nasa.gov
+#[derive(Clone, Debug)]
+pub struct BackfillConfig {
+    pub flr_from: NaiveDate,
+    pub chunk_days: u64,
+    pub chunks_per_run: u32,
+    // Архив APOD догружается на столько лет назад от сегодняшнего дня
+    pub apod_years: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct NeoConfig {
+    pub ahead_days: u64,
+    // Карточка объекта из lookup считается свежей object_ttl секунд;
+    // планировщик обновляет не больше refresh_batch устаревших за запуск
+    pub object_ttl: u64,
+    pub refresh_batch: u32,
 }
 
 #[derive(Clone, Debug)]
@@ -37,21 +94,91 @@ pub struct RetryConfig {
     pub initial_delay_ms: u64,
     pub max_delay_ms: u64,
     pub backoff_multiplier: f64,
+    pub retry_after_max_seconds: u64,
 }
 
 #[derive(Clone, Debug)]
 pub struct RateLimitConfig {
     pub requests_per_minute: u32,
     pub burst_size: u32,
+    pub max_wait_ms: u64,
+    pub hosts: BTreeMap<String, HostRateLimit>,
+    pub quota_low_percent: u32,
+    pub quota_slowdown_ms: u64,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct HostRateLimit {
+    pub requests_per_minute: u32,
+    pub burst_size: u32,
+}
+
+impl RateLimitConfig {
+    // Хосты без отдельной настройки получают общий бюджет, но каждый свой
+    pub fn for_host(&self, host: &str) -> HostRateLimit {
+        self.hosts.get(host).copied().unwrap_or(HostRateLimit {
+            requests_per_minute: self.requests_per_minute,
+            burst_size: self.burst_size,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BreakerConfig {
+    pub failure_threshold: u32,
+    pub cooldown_seconds: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct JobQueueConfig {
+    pub workers: u32,
+    pub poll_interval_ms: u64,
+    pub max_attempts: u32,
+    pub retry_base_seconds: u64,
+    pub retry_max_seconds: u64,
+    pub lease_seconds: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct ReadCacheConfig {
+    pub iss_last_ttl: Duration,
+    pub summary_ttl: Duration,
+    pub osdr_list_ttl: Duration,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetentionPolicy {
+    KeepAll,
+    KeepDays(u32),
+}
+
+#[derive(Clone, Debug)]
+pub struct CacheRetention {
+    pub policies: BTreeMap<String, RetentionPolicy>,
+    pub prune_every_seconds: u64,
 }
 
 impl Config {
     pub fn from_env() -> Result<Self, String> {
         dotenvy::dotenv().ok();
-        
+
+        let fetch_intervals = FetchIntervals {
+            osdr: env_u64("FETCH_EVERY_SECONDS", 600),
+            iss: env_u64("ISS_EVERY_SECONDS", 120),
+            apod: env_u64("APOD_EVERY_SECONDS", 43200),
+            neo: env_u64("NEO_EVERY_SECONDS", 7200),
+            donki: env_u64("DONKI_EVERY_SECONDS", 3600),
+            spacex: env_u64("SPACEX_EVERY_SECONDS", 3600),
+            jwst: env_u64("JWST_EVERY_SECONDS", 21600),
+            backfill: env_u64("BACKFILL_EVERY_SECONDS", 3600),
+        };
+
         Ok(Config {
             database_url: std::env::var("DATABASE_URL")
                 .map_err(|_| "DATABASE_URL is required")?,
+            // Каждая периодическая задача держит соединение с advisory lock и берёт ещё одно
+            // под свои запросы: 12 задач × 2, воркеры очереди и запас под HTTP
+            db_max_connections: env_u64("DB_MAX_CONNECTIONS", 40).max(5) as u32,
             redis_url: std::env::var("REDIS_URL")
                 .unwrap_or_else(|_| "redis://redis:6379".to_string()),
             nasa_url: std::env::var("NASA_API_URL")
@@ -61,13 +188,49 @@ impl Config {
                 .unwrap_or_else(|_| "https://api.wheretheiss.at/v1/satellites/25544".to_string()),
             user_agent: std::env::var("USER_AGENT")
                 .unwrap_or_else(|_| "Cassiopeya-Space-Data-Collector/1.0".to_string()),
-            fetch_intervals: FetchIntervals {
-                osdr: env_u64("FETCH_EVERY_SECONDS", 600),
-                iss: env_u64("ISS_EVERY_SECONDS", 120),
-                apod: env_u64("APOD_EVERY_SECONDS", 43200),
-                neo: env_u64("NEO_EVERY_SECONDS", 7200),
-                donki: env_u64("DONKI_EVERY_SECONDS", 3600),
-                spacex: env_u64("SPACEX_EVERY_SECONDS", 3600),
+            freshness: parse_freshness(
+                &fetch_intervals,
+                &std::env::var("CACHE_FRESHNESS").unwrap_or_default(),
+            )?,
+            fetch_intervals,
+            manual_refresh_min_interval: env_u64("MANUAL_REFRESH_MIN_INTERVAL_SECONDS", 60),
+            jwst: JwstConfig {
+                host: std::env::var("JWST_HOST").unwrap_or_else(|_| "https://api.jwstapi.com".to_string()),
+                api_key: std::env::var("JWST_API_KEY").unwrap_or_default(),
+                email: std::env::var("JWST_EMAIL").ok().filter(|s| !s.is_empty()),
+                program_id: std::env::var("JWST_PROGRAM_ID").ok().filter(|s| !s.is_empty()),
+                sync_pages: env_u64("JWST_SYNC_PAGES", 3) as u32,
+                per_page: env_u64("JWST_PER_PAGE", 60) as u32,
+            },
+            astronomy: AstronomyConfig {
+                host: std::env::var("ASTRO_HOST").unwrap_or_else(|_| "https://api.astronomyapi.com".to_string()),
+                app_id: std::env::var("ASTRO_APP_ID").unwrap_or_default(),
+                app_secret: std::env::var("ASTRO_APP_SECRET").unwrap_or_default(),
+                // События API отдаёт только для Солнца и Луны
+                bodies: std::env::var("ASTRO_BODIES")
+                    .unwrap_or_else(|_| "sun,moon".to_string())
+                    .split(',')
+                    .map(|s| s.trim().to_lowercase())
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+                events_ttl: Duration::from_secs(env_u64("CACHE_TTL_ASTRO_EVENTS_SECONDS", 21600)),
+            },
+            backfill: BackfillConfig {
+                // Каталог вспышек DONKI начинается в 2010 году
+                flr_from: env_date("FLR_BACKFILL_FROM", NaiveDate::from_ymd_opt(2010, 1, 1).unwrap())?,
+                chunk_days: env_u64("BACKFILL_CHUNK_DAYS", 30).clamp(1, 365),
+                chunks_per_run: env_u64("BACKFILL_CHUNKS_PER_RUN", 3) as u32,
+                apod_years: env_u64("APOD_BACKFILL_YEARS", 5).clamp(1, 40) as u32,
+            },
+            neo: NeoConfig {
+                ahead_days: env_u64("NEO_AHEAD_DAYS", 7),
+                object_ttl: env_u64("NEO_OBJECT_TTL_SECONDS", 86400),
+                refresh_batch: env_u64("NEO_OBJECT_REFRESH_BATCH", 20) as u32,
+            },
+            read_cache: ReadCacheConfig {
+                iss_last_ttl: Duration::from_secs(env_u64("CACHE_TTL_ISS_LAST_SECONDS", 15)),
+                summary_ttl: Duration::from_secs(env_u64("CACHE_TTL_SUMMARY_SECONDS", 60)),
+                osdr_list_ttl: Duration::from_secs(env_u64("CACHE_TTL_OSDR_LIST_SECONDS", 300)),
             },
             timeouts: Timeouts {
                 http_connect: Duration::from_secs(10),
@@ -79,10 +242,33 @@ impl Config {
                 initial_delay_ms: env_u64("RETRY_INITIAL_DELAY_MS", 1000),
                 max_delay_ms: env_u64("RETRY_MAX_DELAY_MS", 10000),
                 backoff_multiplier: 2.0,
+                retry_after_max_seconds: env_u64("RETRY_AFTER_MAX_SECONDS", 120),
             },
             rate_limit: RateLimitConfig {
                 requests_per_minute: env_u64("RATE_LIMIT_PER_MINUTE", 60) as u32,
                 burst_size: env_u64("RATE_LIMIT_BURST", 10) as u32,
+                max_wait_ms: env_u64("RATE_LIMIT_MAX_WAIT_MS", 5000),
+                hosts: parse_host_limits(&std::env::var("RATE_LIMIT_HOSTS").unwrap_or_default())?,
+                quota_low_percent: env_u64("RATE_LIMIT_QUOTA_LOW_PERCENT", 10) as u32,
+                quota_slowdown_ms: env_u64("RATE_LIMIT_QUOTA_SLOWDOWN_MS", 2000),
+            },
+            breaker: BreakerConfig {
+                failure_threshold: env_u64("BREAKER_FAILURE_THRESHOLD", 5).max(1) as u32,
+                cooldown_seconds: env_u64("BREAKER_COOLDOWN_SECONDS", 60),
+            },
+            jobs: JobQueueConfig {
+                workers: env_u64("JOB_WORKERS", 2) as u32,
+                poll_interval_ms: env_u64("JOB_POLL_INTERVAL_MS", 1000),
+                max_attempts: env_u64("JOB_MAX_ATTEMPTS", 5) as u32,
+                retry_base_seconds: env_u64("JOB_RETRY_BASE_SECONDS", 30),
+                retry_max_seconds: env_u64("JOB_RETRY_MAX_SECONDS", 3600),
+                lease_seconds: env_u64("JOB_LEASE_SECONDS", 900),
+            },
+            shutdown_grace_seconds: env_u64("SHUTDOWN_GRACE_SECONDS", 25),
+            refresh_concurrency: env_u64("REFRESH_CONCURRENCY", 3) as usize,
+            retention: CacheRetention {
+                policies: parse_retention(&std::env::var("CACHE_RETENTION").unwrap_or_default())?,
+                prune_every_seconds: env_u64("CACHE_PRUNE_EVERY_SECONDS", 86400),
             },
         })
     }
@@ -92,5 +278,109 @@ fn env_u64(k: &str, d: u64) -> u64 {
     std::env::var(k).ok().and_then(|s| s.parse().ok()).unwrap_or(d)
 }
 
+fn env_date(k: &str, d: NaiveDate) -> Result<NaiveDate, String> {
+    match std::env::var(k).ok().filter(|s| !s.trim().is_empty()) {
+        Some(s) => NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").map_err(|_| format!("{}: expected YYYY-MM-DD, got {}", k, s)),
+        None => Ok(d),
+    }
+}
+
+// RATE_LIMIT_HOSTS=api.nasa.gov=30/5,api.spacexdata.com=120/20 — запросов в минуту / burst
+fn parse_host_limits(spec: &str) -> Result<BTreeMap<String, HostRateLimit>, String> {
+    let mut hosts = BTreeMap::new();
+
+    for part in spec.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        let (host, value) = part
+            .split_once('=')
+            .ok_or_else(|| format!("RATE_LIMIT_HOSTS: expected host=rpm/burst, got {}", part))?;
+        let (rpm, burst) = value.split_once('/').unwrap_or((value, value));
+        let parse = |v: &str| {
+            v.trim()
+                .parse::<u32>()
+                .map_err(|_| format!("RATE_LIMIT_HOSTS: invalid limit {}", value))
+        };
+        hosts.insert(
+            host.trim().to_string(),
+            HostRateLimit {
+                requests_per_minute: parse(rpm)?,
+                burst_size: parse(burst)?,
+            },
+        );
+    }
+
+    Ok(hosts)
+}
+
+// CACHE_RETENTION=neo=90d,spacex=30d,apod=all — переопределяет значения по умолчанию
+fn parse_retention(spec: &str) -> Result<BTreeMap<String, RetentionPolicy>, String> {
+    let mut policies: BTreeMap<String, RetentionPolicy> = [
+        ("apod", RetentionPolicy::KeepAll),
+        ("neo", RetentionPolicy::KeepDays(90)),
+        ("flr", RetentionPolicy::KeepDays(365)),
+        ("cme", RetentionPolicy::KeepDays(365)),
+        ("gst", RetentionPolicy::KeepDays(365)),
+        ("sep", RetentionPolicy::KeepDays(365)),
+        ("ips", RetentionPolicy::KeepDays(365)),
+        ("hss", RetentionPolicy::KeepDays(365)),
+        ("spacex", RetentionPolicy::KeepDays(90)),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_string(), v))
+    .collect();
+
+    for part in spec.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        let (source, value) = part
+            .split_once('=')
+            .ok_or_else(|| format!("CACHE_RETENTION: expected source=policy, got {}", part))?;
+        let policy = match value.trim() {
+            "all" => RetentionPolicy::KeepAll,
+            v => v
+                .trim_end_matches('d')
+                .parse::<u32>()
+                .map(RetentionPolicy::KeepDays)
+                .map_err(|_| format!("CACHE_RETENTION: invalid policy {}", v))?,
+        };
+        // Опечатка в имени источника иначе молча оставила бы политику по умолчанию
+        let source = source.trim();
+        if !policies.contains_key(source) {
+            return Err(format!("CACHE_RETENTION: unknown source {}", source));
+        }
+        policies.insert(source.to_string(), policy);
+    }
+
+    Ok(policies)
+}
+
+// Окно свежести источника в секундах: по умолчанию два интервала опроса,
+// CACHE_FRESHNESS=flr=86400,apod=172800 переопределяет
+fn parse_freshness(intervals: &FetchIntervals, spec: &str) -> Result<BTreeMap<String, u64>, String> {
+    let mut windows: BTreeMap<String, u64> = [
+        ("apod", intervals.apod),
+        ("neo", intervals.neo),
+        ("flr", intervals.donki),
+        ("cme", intervals.donki),
+        ("gst", intervals.donki),
+        ("sep", intervals.donki),
+        ("ips", intervals.donki),
+        ("hss", intervals.donki),
+        ("spacex", intervals.spacex),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_string(), v.saturating_mul(2)))
+    .collect();
+
+    for part in spec.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        let (source, value) = part
+            .split_once('=')
+            .ok_or_else(|| format!("CACHE_FRESHNESS: expected source=seconds, got {}", part))?;
+        let secs = value
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| format!("CACHE_FRESHNESS: invalid seconds {}", value))?;
+        windows.insert(source.trim().to_string(), secs);
+    }
+
+    Ok(windows)
+}
 
 
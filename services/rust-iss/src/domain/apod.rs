@@ -0,0 +1,25 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::space::Apod;
+
+// Первый выпуск APOD — раньше этой даты архив пуст
+pub fn apod_first_date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(1995, 6, 16).unwrap()
+}
+
+#[derive(Debug, Clone)]
+pub struct ApodFilter {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub media_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApodPage {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub random: bool,
+    pub count: usize,
+    pub items: Vec<Apod>,
+}
@@ -0,0 +1,94 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// Ответ AstronomyAPI /bodies/events/{body}: data.table.rows[].cells[]
+#[derive(Debug, Clone, Deserialize)]
+pub struct AstroEventsResponse {
+    pub data: AstroEventsData,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AstroEventsData {
+    pub table: AstroTable,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AstroTable {
+    #[serde(default)]
+    pub rows: Vec<AstroRow>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AstroRow {
+    pub entry: AstroEntry,
+    #[serde(default)]
+    pub cells: Vec<AstroCell>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AstroEntry {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AstroCell {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default, rename = "eventHighlights")]
+    pub highlights: Value,
+    #[serde(default)]
+    pub rise: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub set: Option<DateTime<Utc>>,
+    #[serde(default, rename = "extraInfo")]
+    pub extra: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AstroEvent {
+    pub body: String,
+    pub kind: String,
+    pub peak: Option<DateTime<Utc>>,
+    pub rise: Option<DateTime<Utc>>,
+    pub set: Option<DateTime<Utc>>,
+    pub highlights: Value,
+    pub extra: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AstroEvents {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub events: Vec<AstroEvent>,
+}
+
+impl AstroEventsResponse {
+    pub fn into_events(self) -> Vec<AstroEvent> {
+        self.data
+            .table
+            .rows
+            .into_iter()
+            .flat_map(|row| {
+                let body = row.entry.id;
+                row.cells.into_iter().map(move |cell| AstroEvent {
+                    body: body.clone(),
+                    peak: cell
+                        .highlights
+                        .pointer("/peak/date")
+                        .and_then(|v| v.as_str())
+                        .and_then(|s| s.parse().ok()),
+                    kind: cell.kind,
+                    rise: cell.rise,
+                    set: cell.set,
+                    highlights: cell.highlights,
+                    extra: cell.extra,
+                })
+            })
+            .collect()
+    }
+}
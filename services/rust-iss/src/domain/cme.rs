@@ -0,0 +1,160 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::space::{CmeAnalysis, DonkiCme, EnlilRun};
+
+const SOLAR_RADIUS_KM: f64 = 695_700.0;
+const AU_KM: f64 = 149_597_870.7;
+// Параметры drag-based model (Vršnak et al., 2013) для среднего солнечного ветра
+const DBM_GAMMA_PER_KM: f64 = 0.2e-7;
+const DBM_WIND_KM_S: f64 = 400.0;
+// Дольше CME до Земли не идут — дальше поиск корня не нужен
+const DBM_MAX_HOURS: f64 = 240.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArrivalSource {
+    Enlil,
+    DragBased,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KpEstimate {
+    pub kp_18: Option<f64>,
+    pub kp_90: Option<f64>,
+    pub kp_135: Option<f64>,
+    pub kp_180: Option<f64>,
+    pub max: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CmeArrival {
+    pub activity_id: String,
+    pub start_time: DateTime<Utc>,
+    pub source_location: Option<String>,
+    pub speed: Option<f64>,
+    pub half_angle: Option<f64>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub arrival_time: DateTime<Utc>,
+    pub source: ArrivalSource,
+    pub glancing_blow: bool,
+    pub kp: Option<KpEstimate>,
+    pub duration_hours: Option<f64>,
+    pub model_completion_time: Option<DateTime<Utc>>,
+    pub link: Option<String>,
+    pub model_link: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CmeArrivals {
+    pub generated_at: DateTime<Utc>,
+    pub since: DateTime<Utc>,
+    pub items: Vec<CmeArrival>,
+}
+
+// Расстояние фронта от Солнца через t секунд после прохождения r0, км:
+// r(t) = ±(1/γ)·ln(1 ± γ(v0 − w)t) + w·t + r0, знак — по знаку v0 − w
+fn dbm_distance(t: f64, v0: f64, r0: f64) -> f64 {
+    let dv = v0 - DBM_WIND_KM_S;
+    let s = if dv >= 0.0 { 1.0 } else { -1.0 };
+    s / DBM_GAMMA_PER_KM * (1.0 + s * DBM_GAMMA_PER_KM * dv * t).ln() + DBM_WIND_KM_S * t + r0
+}
+
+// Время прихода на 1 а.е. по DBM, начиная с момента прохождения 21.5 R☉
+pub fn drag_based_arrival(time21_5: DateTime<Utc>, speed_km_s: f64) -> Option<DateTime<Utc>> {
+    if !(speed_km_s.is_finite() && speed_km_s > 0.0) {
+        return None;
+    }
+    let r0 = 21.5 * SOLAR_RADIUS_KM;
+    let (mut lo, mut hi) = (0.0f64, DBM_MAX_HOURS * 3600.0);
+    if dbm_distance(hi, speed_km_s, r0) < AU_KM {
+        return None;
+    }
+    while hi - lo > 60.0 {
+        let mid = (lo + hi) / 2.0;
+        if dbm_distance(mid, speed_km_s, r0) < AU_KM {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    Some(time21_5 + Duration::seconds(hi.round() as i64))
+}
+
+fn kp_estimate(run: &EnlilRun) -> Option<KpEstimate> {
+    let values = [run.kp_18, run.kp_90, run.kp_135, run.kp_180];
+    let max = values.iter().flatten().copied().reduce(f64::max)?;
+    Some(KpEstimate {
+        kp_18: run.kp_18,
+        kp_90: run.kp_90,
+        kp_135: run.kp_135,
+        kp_180: run.kp_180,
+        max: Some(max),
+    })
+}
+
+// Земля в HEEQ лежит у долготы 0 и широты в пределах ±7°;
+// считаем выброс направленным к Земле, если конус её накрывает
+fn earth_directed(a: &CmeAnalysis) -> bool {
+    match (a.latitude, a.longitude, a.half_angle) {
+        (Some(lat), Some(lon), Some(half)) => lon.abs() <= half && (lat.abs() - 7.0).max(0.0) <= half,
+        _ => false,
+    }
+}
+
+impl DonkiCme {
+    // Основной анализ: отмеченный как наиболее точный, иначе последний
+    pub fn primary_analysis(&self) -> Option<&CmeAnalysis> {
+        self.cme_analyses
+            .iter()
+            .find(|a| a.is_most_accurate)
+            .or(self.cme_analyses.last())
+    }
+
+    // Прогноз прихода к Земле: последний прогон ENLIL с ударом по Земле; без прогонов ENLIL —
+    // оценка DBM для направленного к Земле выброса. Если ENLIL считал и удара не нашёл — None
+    pub fn earth_arrival(&self) -> Option<CmeArrival> {
+        let analysis = self.primary_analysis()?;
+        let runs: Vec<&EnlilRun> = self.cme_analyses.iter().flat_map(|a| a.enlil_list.iter()).collect();
+
+        let base = |arrival_time, source| CmeArrival {
+            activity_id: self.activity_id.clone(),
+            start_time: self.start_time,
+            source_location: self.source_location.clone(),
+            speed: analysis.speed,
+            half_angle: analysis.half_angle,
+            latitude: analysis.latitude,
+            longitude: analysis.longitude,
+            arrival_time,
+            source,
+            glancing_blow: false,
+            kp: None,
+            duration_hours: None,
+            model_completion_time: None,
+            link: self.link.clone(),
+            model_link: None,
+        };
+
+        if !runs.is_empty() {
+            let run = runs
+                .into_iter()
+                .filter(|r| r.estimated_shock_arrival_time.is_some())
+                .max_by_key(|r| r.model_completion_time)?;
+            return Some(CmeArrival {
+                glancing_blow: run.is_earth_gb.unwrap_or(false),
+                kp: kp_estimate(run),
+                duration_hours: run.estimated_duration,
+                model_completion_time: run.model_completion_time,
+                model_link: run.link.clone(),
+                ..base(run.estimated_shock_arrival_time?, ArrivalSource::Enlil)
+            });
+        }
+
+        if !earth_directed(analysis) {
+            return None;
+        }
+        let arrival = drag_based_arrival(analysis.time21_5?, analysis.speed?)?;
+        Some(base(arrival, ArrivalSource::DragBased))
+    }
+}
@@ -0,0 +1,457 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// Локальные эфемериды Солнца и Луны без внешних API.
+// Солнце — формулы Astronomical Almanac низкой точности (~0.01°),
+// Луна — главные члены рядов Meeus, гл. 47 (~0.3° по долготе).
+// TT принимается равным UTC: ошибка ~70 с на результат почти не влияет.
+
+const J2000: f64 = 2451545.0;
+const SYNODIC_MONTH_DAYS: f64 = 29.530588853;
+const EARTH_RADIUS_KM: f64 = 6378.14;
+
+// Высота центра светила в момент восхода/захода с учётом рефракции и радиуса диска
+const SUN_HORIZON_DEG: f64 = -0.8333;
+const CIVIL_DEG: f64 = -6.0;
+const NAUTICAL_DEG: f64 = -12.0;
+const ASTRONOMICAL_DEG: f64 = -18.0;
+
+const SCAN_STEP_MINUTES: i64 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Equatorial {
+    // Прямое восхождение и склонение, градусы
+    pub ra: f64,
+    pub dec: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ecliptic {
+    pub lon: f64,
+    pub lat: f64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Horizontal {
+    pub altitude: f64,
+    // От севера через восток, градусы
+    pub azimuth: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiseSet {
+    pub rise: Option<DateTime<Utc>>,
+    pub set: Option<DateTime<Utc>>,
+    pub transit: Option<DateTime<Utc>>,
+    pub always_up: bool,
+    pub always_down: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Twilight {
+    pub dawn: Option<DateTime<Utc>>,
+    pub dusk: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Twilights {
+    pub civil: Twilight,
+    pub nautical: Twilight,
+    pub astronomical: Twilight,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoonPhase {
+    pub name: String,
+    // Освещённая доля диска 0..1
+    pub illumination: f64,
+    pub phase_angle: f64,
+    // Элонгация Луны от Солнца по долготе 0..360, 0 — новолуние
+    pub elongation: f64,
+    pub age_days: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SunReport {
+    pub right_ascension: f64,
+    pub declination: f64,
+    pub distance_au: f64,
+    #[serde(flatten)]
+    pub position: Horizontal,
+    #[serde(flatten)]
+    pub rise_set: RiseSet,
+    pub twilight: Twilights,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoonReport {
+    pub right_ascension: f64,
+    pub declination: f64,
+    pub distance_km: f64,
+    #[serde(flatten)]
+    pub position: Horizontal,
+    #[serde(flatten)]
+    pub rise_set: RiseSet,
+    pub phase: MoonPhase,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkyReport {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub date: NaiveDate,
+    // Сутки наблюдателя по местному среднему солнечному времени, в UTC
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    // Момент, для которого посчитаны положения
+    pub at: DateTime<Utc>,
+    pub sun: SunReport,
+    pub moon: MoonReport,
+}
+
+fn norm360(x: f64) -> f64 {
+    x.rem_euclid(360.0)
+}
+
+fn sin_d(x: f64) -> f64 {
+    x.to_radians().sin()
+}
+
+fn cos_d(x: f64) -> f64 {
+    x.to_radians().cos()
+}
+
+pub fn julian_day(t: DateTime<Utc>) -> f64 {
+    t.timestamp_millis() as f64 / 86_400_000.0 + 2440587.5
+}
+
+fn centuries(t: DateTime<Utc>) -> f64 {
+    (julian_day(t) - J2000) / 36525.0
+}
+
+fn obliquity(t: DateTime<Utc>) -> f64 {
+    23.439291 - 0.0130042 * centuries(t)
+}
+
+fn ecliptic_to_equatorial(ecl: Ecliptic, eps: f64) -> Equatorial {
+    let ra = (sin_d(ecl.lon) * cos_d(eps) - (ecl.lat.to_radians().tan()) * sin_d(eps))
+        .atan2(cos_d(ecl.lon))
+        .to_degrees();
+    let dec = (sin_d(ecl.lat) * cos_d(eps) + cos_d(ecl.lat) * sin_d(eps) * sin_d(ecl.lon))
+        .asin()
+        .to_degrees();
+    Equatorial { ra: norm360(ra), dec }
+}
+
+// Среднее гринвичское звёздное время, градусы (Meeus 12.4)
+pub fn gmst(t: DateTime<Utc>) -> f64 {
+    let d = julian_day(t) - J2000;
+    let tc = d / 36525.0;
+    norm360(280.46061837 + 360.98564736629 * d + 0.000387933 * tc * tc - tc * tc * tc / 38_710_000.0)
+}
+
+pub fn to_horizontal(eq: Equatorial, t: DateTime<Utc>, lat: f64, lon: f64) -> Horizontal {
+    let ha = norm360(gmst(t) + lon - eq.ra);
+    let alt = (sin_d(lat) * sin_d(eq.dec) + cos_d(lat) * cos_d(eq.dec) * cos_d(ha))
+        .asin()
+        .to_degrees();
+    let az = (-sin_d(ha))
+        .atan2(eq.dec.to_radians().tan() * cos_d(lat) - sin_d(lat) * cos_d(ha))
+        .to_degrees();
+    Horizontal {
+        altitude: alt,
+        azimuth: norm360(az),
+    }
+}
+
+// Геоцентрическая эклиптическая долгота Солнца и расстояние в а.е.
+pub fn sun_ecliptic(t: DateTime<Utc>) -> (Ecliptic, f64) {
+    let n = julian_day(t) - J2000;
+    let l = norm360(280.460 + 0.9856474 * n);
+    let g = norm360(357.528 + 0.9856003 * n);
+    let lon = norm360(l + 1.915 * sin_d(g) + 0.020 * sin_d(2.0 * g));
+    let r = 1.00014 - 0.01671 * cos_d(g) - 0.00014 * cos_d(2.0 * g);
+    (Ecliptic { lon, lat: 0.0 }, r)
+}
+
+pub fn sun_equatorial(t: DateTime<Utc>) -> Equatorial {
+    let n = julian_day(t) - J2000;
+    let eps = 23.439 - 0.0000004 * n;
+    ecliptic_to_equatorial(sun_ecliptic(t).0, eps)
+}
+
+struct MoonArgs {
+    d: f64,
+    m: f64,
+    mp: f64,
+    f: f64,
+    lp: f64,
+}
+
+fn moon_args(t: DateTime<Utc>) -> MoonArgs {
+    let tc = centuries(t);
+    MoonArgs {
+        lp: norm360(218.3164477 + 481267.88123421 * tc),
+        d: norm360(297.8501921 + 445267.1114034 * tc),
+        m: norm360(357.5291092 + 35999.0502909 * tc),
+        mp: norm360(134.9633964 + 477198.8675055 * tc),
+        f: norm360(93.2720950 + 483202.0175233 * tc),
+    }
+}
+
+// Геоцентрические эклиптические координаты Луны и расстояние в км
+pub fn moon_ecliptic(t: DateTime<Utc>) -> (Ecliptic, f64) {
+    let MoonArgs { d, m, mp, f, lp } = moon_args(t);
+
+    let lon = lp
+        + 6.288774 * sin_d(mp)
+        + 1.274027 * sin_d(2.0 * d - mp)
+        + 0.658314 * sin_d(2.0 * d)
+        + 0.213618 * sin_d(2.0 * mp)
+        - 0.185116 * sin_d(m)
+        - 0.114332 * sin_d(2.0 * f)
+        + 0.058793 * sin_d(2.0 * d - 2.0 * mp)
+        + 0.057066 * sin_d(2.0 * d - m - mp)
+        + 0.053322 * sin_d(2.0 * d + mp)
+        + 0.045758 * sin_d(2.0 * d - m)
+        - 0.040923 * sin_d(m - mp)
+        - 0.034720 * sin_d(d)
+        - 0.030383 * sin_d(m + mp)
+        + 0.015327 * sin_d(2.0 * d - 2.0 * f)
+        - 0.012528 * sin_d(mp + 2.0 * f)
+        + 0.010980 * sin_d(mp - 2.0 * f)
+        + 0.010675 * sin_d(4.0 * d - mp)
+        + 0.010034 * sin_d(3.0 * mp)
+        + 0.008548 * sin_d(4.0 * d - 2.0 * mp);
+
+    let lat = 5.128122 * sin_d(f)
+        + 0.280602 * sin_d(mp + f)
+        + 0.277693 * sin_d(mp - f)
+        + 0.173237 * sin_d(2.0 * d - f)
+        + 0.055413 * sin_d(2.0 * d - mp + f)
+        + 0.046271 * sin_d(2.0 * d - mp - f)
+        + 0.032573 * sin_d(2.0 * d + f)
+        + 0.017198 * sin_d(2.0 * mp + f)
+        + 0.009266 * sin_d(2.0 * d + mp - f)
+        + 0.008822 * sin_d(2.0 * mp - f);
+
+    let dist = 385000.56
+        - 20905.355 * cos_d(mp)
+        - 3699.111 * cos_d(2.0 * d - mp)
+        - 2955.968 * cos_d(2.0 * d)
+        - 569.925 * cos_d(2.0 * mp)
+        + 48.888 * cos_d(m)
+        - 3.149 * cos_d(2.0 * f)
+        + 246.158 * cos_d(2.0 * d - 2.0 * mp)
+        - 152.138 * cos_d(2.0 * d - m - mp)
+        - 170.733 * cos_d(2.0 * d + mp)
+        - 204.586 * cos_d(2.0 * d - m)
+        - 129.620 * cos_d(m - mp)
+        + 108.743 * cos_d(d)
+        + 104.755 * cos_d(m + mp);
+
+    (Ecliptic { lon: norm360(lon), lat }, dist)
+}
+
+pub fn moon_equatorial(t: DateTime<Utc>) -> (Equatorial, f64) {
+    let (ecl, dist) = moon_ecliptic(t);
+    (ecliptic_to_equatorial(ecl, obliquity(t)), dist)
+}
+
+// Топоцентрическая высота Луны: поправка за параллакс до 1°
+fn moon_horizontal(t: DateTime<Utc>, lat: f64, lon: f64) -> Horizontal {
+    let (eq, dist) = moon_equatorial(t);
+    let mut h = to_horizontal(eq, t, lat, lon);
+    let parallax = (EARTH_RADIUS_KM / dist).asin().to_degrees();
+    h.altitude -= parallax * cos_d(h.altitude);
+    h
+}
+
+fn sun_horizontal(t: DateTime<Utc>, lat: f64, lon: f64) -> Horizontal {
+    to_horizontal(sun_equatorial(t), t, lat, lon)
+}
+
+pub fn moon_phase(t: DateTime<Utc>) -> MoonPhase {
+    let MoonArgs { d, m, mp, .. } = moon_args(t);
+    // Фазовый угол, Meeus 48.4
+    let i = norm360(
+        180.0 - d - 6.289 * sin_d(mp) + 2.100 * sin_d(m)
+            - 1.274 * sin_d(2.0 * d - mp)
+            - 0.658 * sin_d(2.0 * d)
+            - 0.214 * sin_d(2.0 * mp)
+            - 0.110 * sin_d(d),
+    );
+    let illumination = (1.0 + cos_d(i)) / 2.0;
+    let elongation = norm360(moon_ecliptic(t).0.lon - sun_ecliptic(t).0.lon);
+
+    let name = match elongation {
+        e if !(11.25..348.75).contains(&e) => "new_moon",
+        e if e < 78.75 => "waxing_crescent",
+        e if e < 101.25 => "first_quarter",
+        e if e < 168.75 => "waxing_gibbous",
+        e if e < 191.25 => "full_moon",
+        e if e < 258.75 => "waning_gibbous",
+        e if e < 281.25 => "last_quarter",
+        _ => "waning_crescent",
+    };
+
+    MoonPhase {
+        name: name.to_string(),
+        illumination,
+        phase_angle: if i > 180.0 { 360.0 - i } else { i },
+        elongation,
+        age_days: elongation / 360.0 * SYNODIC_MONTH_DAYS,
+    }
+}
+
+// Пересечения высоты h0 внутри окна: шаг сетки SCAN_STEP_MINUTES, затем бисекция до секунды
+fn crossings<F: Fn(DateTime<Utc>) -> f64>(
+    altitude: &F,
+    h0: f64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>, bool) {
+    let step = Duration::minutes(SCAN_STEP_MINUTES);
+    let mut rise = None;
+    let mut set = None;
+    let mut t0 = start;
+    let mut a0 = altitude(t0) - h0;
+    let above_at_start = a0 > 0.0;
+
+    while t0 < end {
+        let t1 = (t0 + step).min(end);
+        let a1 = altitude(t1) - h0;
+        if (a0 <= 0.0) != (a1 <= 0.0) {
+            let (mut lo, mut hi) = (t0, t1);
+            let rising = a1 > 0.0;
+            while hi - lo > Duration::seconds(1) {
+                let mid = lo + (hi - lo) / 2;
+                let above = altitude(mid) - h0 > 0.0;
+                if above == rising {
+                    hi = mid;
+                } else {
+                    lo = mid;
+                }
+            }
+            if rising && rise.is_none() {
+                rise = Some(hi);
+            } else if !rising && set.is_none() {
+                set = Some(hi);
+            }
+        }
+        t0 = t1;
+        a0 = a1;
+    }
+
+    (rise, set, above_at_start)
+}
+
+// Момент наибольшей высоты — верхняя кульминация внутри окна
+fn transit<F: Fn(DateTime<Utc>) -> f64>(altitude: &F, start: DateTime<Utc>, end: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let step = Duration::minutes(SCAN_STEP_MINUTES);
+    let mut best = start;
+    let mut best_alt = altitude(start);
+    let mut t = start;
+    while t < end {
+        t += step;
+        let a = altitude(t.min(end));
+        if a > best_alt {
+            best = t.min(end);
+            best_alt = a;
+        }
+    }
+    // Кульминация на краю окна означает, что она пришлась на соседние сутки
+    if best == start || best >= end {
+        return None;
+    }
+
+    let (mut lo, mut hi) = (best - step, best + step);
+    while hi - lo > Duration::seconds(2) {
+        let m1 = lo + (hi - lo) / 3;
+        let m2 = hi - (hi - lo) / 3;
+        if altitude(m1) < altitude(m2) {
+            lo = m1;
+        } else {
+            hi = m2;
+        }
+    }
+    Some(lo + (hi - lo) / 2)
+}
+
+fn rise_set<F: Fn(DateTime<Utc>) -> f64>(altitude: &F, h0: f64, start: DateTime<Utc>, end: DateTime<Utc>) -> RiseSet {
+    let (rise, set, above_at_start) = crossings(altitude, h0, start, end);
+    let none = rise.is_none() && set.is_none();
+    RiseSet {
+        rise,
+        set,
+        transit: transit(altitude, start, end),
+        always_up: none && above_at_start,
+        always_down: none && !above_at_start,
+    }
+}
+
+fn twilight<F: Fn(DateTime<Utc>) -> f64>(altitude: &F, h0: f64, start: DateTime<Utc>, end: DateTime<Utc>) -> Twilight {
+    let (dawn, dusk, _) = crossings(altitude, h0, start, end);
+    Twilight { dawn, dusk }
+}
+
+// Сутки наблюдателя: полночь по местному среднему солнечному времени
+pub fn observer_day(date: NaiveDate, lon: f64) -> (DateTime<Utc>, DateTime<Utc>) {
+    let midnight = date.and_time(NaiveTime::MIN).and_utc();
+    let start = midnight - Duration::seconds((lon / 15.0 * 3600.0).round() as i64);
+    (start, start + Duration::days(1))
+}
+
+pub fn sun_rise_set(date: NaiveDate, lat: f64, lon: f64) -> RiseSet {
+    let (start, end) = observer_day(date, lon);
+    let alt = |t| sun_horizontal(t, lat, lon).altitude;
+    rise_set(&alt, SUN_HORIZON_DEG, start, end)
+}
+
+pub fn twilights(date: NaiveDate, lat: f64, lon: f64) -> Twilights {
+    let (start, end) = observer_day(date, lon);
+    let alt = |t| sun_horizontal(t, lat, lon).altitude;
+    Twilights {
+        civil: twilight(&alt, CIVIL_DEG, start, end),
+        nautical: twilight(&alt, NAUTICAL_DEG, start, end),
+        astronomical: twilight(&alt, ASTRONOMICAL_DEG, start, end),
+    }
+}
+
+// Для Луны высота уже топоцентрическая, поэтому h0 — только рефракция и полудиаметр
+pub fn moon_rise_set(date: NaiveDate, lat: f64, lon: f64) -> RiseSet {
+    let (start, end) = observer_day(date, lon);
+    let alt = |t| moon_horizontal(t, lat, lon).altitude;
+    rise_set(&alt, -0.8333, start, end)
+}
+
+pub fn sky_report(lat: f64, lon: f64, date: NaiveDate, at: DateTime<Utc>) -> SkyReport {
+    let (window_start, window_end) = observer_day(date, lon);
+    let sun_eq = sun_equatorial(at);
+    let (moon_eq, moon_dist) = moon_equatorial(at);
+
+    SkyReport {
+        latitude: lat,
+        longitude: lon,
+        date,
+        window_start,
+        window_end,
+        at,
+        sun: SunReport {
+            right_ascension: sun_eq.ra,
+            declination: sun_eq.dec,
+            distance_au: sun_ecliptic(at).1,
+            position: sun_horizontal(at, lat, lon),
+            rise_set: sun_rise_set(date, lat, lon),
+            twilight: twilights(date, lat, lon),
+        },
+        moon: MoonReport {
+            right_ascension: moon_eq.ra,
+            declination: moon_eq.dec,
+            distance_km: moon_dist,
+            position: moon_horizontal(at, lat, lon),
+            rise_set: moon_rise_set(date, lat, lon),
+            phase: moon_phase(at),
+        },
+    }
+}
@@ -9,9 +9,6 @@ pub enum ApiError {
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
     
-    #[error("HTTP client error: {0}")]
-    HttpClient(#[from] reqwest::Error),
-    
     #[error("Redis error: {0}")]
     Redis(String),
     
@@ -29,6 +26,12 @@ pub enum ApiError {
     
     #[error("External API error: {0}")]
     ExternalApi(String),
+
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
+
+    #[error("Gateway timeout: {0}")]
+    GatewayTimeout(String),
 }
 
 impl IntoResponse for ApiError {
@@ -38,10 +41,6 @@ impl IntoResponse for ApiError {
                 tracing::error!("Database error: {}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {}", e))
             }
-            ApiError::HttpClient(e) => {
-                tracing::error!("HTTP client error: {}", e);
-                (StatusCode::BAD_GATEWAY, format!("External service error: {}", e))
-            }
             ApiError::Redis(msg) => {
                 tracing::error!("Redis error: {}", msg);
                 (StatusCode::INTERNAL_SERVER_ERROR, format!("Cache error: {}", msg))
@@ -63,6 +62,14 @@ impl IntoResponse for ApiError {
                 tracing::error!("External API error: {}", msg);
                 (StatusCode::BAD_GATEWAY, msg)
             }
+            ApiError::ServiceUnavailable(msg) => {
+                tracing::warn!("Service unavailable: {}", msg);
+                (StatusCode::SERVICE_UNAVAILABLE, msg)
+            }
+            ApiError::GatewayTimeout(msg) => {
+                tracing::warn!("Gateway timeout: {}", msg);
+                (StatusCode::GATEWAY_TIMEOUT, msg)
+            }
         };
 
         let body = Json(json!({
@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+pub const FLARE_CLASSES: [char; 5] = ['A', 'B', 'C', 'M', 'X'];
+
+// Класс рентгеновской вспышки GOES: буква задаёт порядок потока, число — множитель.
+// M2.5 = 2.5e-5 Вт/м², X10 = 1e-3 Вт/м²
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FlareClass {
+    pub letter: char,
+    pub magnitude: f64,
+}
+
+impl FlareClass {
+    pub fn parse(class_type: &str) -> Option<Self> {
+        let s = class_type.trim();
+        let letter = s.chars().next()?.to_ascii_uppercase();
+        if !FLARE_CLASSES.contains(&letter) {
+            return None;
+        }
+        let rest = s[1..].trim();
+        let magnitude = if rest.is_empty() { 1.0 } else { rest.parse::<f64>().ok()? };
+        (magnitude.is_finite() && magnitude > 0.0).then_some(Self { letter, magnitude })
+    }
+
+    // Пиковый поток 1–8 Å, Вт/м²
+    pub fn flux(&self) -> f64 {
+        let base = match self.letter {
+            'A' => 1e-8,
+            'B' => 1e-7,
+            'C' => 1e-6,
+            'M' => 1e-5,
+            _ => 1e-4,
+        };
+        base * self.magnitude
+    }
+
+    pub fn label(&self) -> String {
+        format!("{}{:.1}", self.letter, self.magnitude)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsBucket {
+    Day,
+    Month,
+}
+
+impl StatsBucket {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "day" | "daily" => Some(StatsBucket::Day),
+            "month" | "monthly" => Some(StatsBucket::Month),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlareSummary {
+    pub activity_id: String,
+    pub class_type: String,
+    pub flux: f64,
+    pub begin_time: DateTime<Utc>,
+    pub peak_time: Option<DateTime<Utc>>,
+    pub source_location: Option<String>,
+    pub active_region_num: Option<i64>,
+}
+
+// Период с отсчётами по классам и самой сильной вспышкой периода
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlareBucket {
+    pub period: NaiveDate,
+    pub total: u64,
+    pub counts: BTreeMap<char, u64>,
+    pub peak_class: Option<String>,
+    pub peak_flux: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlareStats {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub bucket: StatsBucket,
+    pub total: u64,
+    pub by_class: BTreeMap<char, u64>,
+    pub buckets: Vec<FlareBucket>,
+    pub strongest: Option<FlareSummary>,
+    // Вспышки с нераспознанным classType — в счётчики не попадают
+    pub unclassified: u64,
+}
@@ -0,0 +1,137 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+// Наблюдение в ответе api.jwstapi.com: {"statusCode":200,"body":[...]}
+#[derive(Debug, Clone, Deserialize)]
+pub struct JwstObservation {
+    #[serde(default, alias = "_id")]
+    pub id: Option<String>,
+    #[serde(default, alias = "observationId")]
+    pub observation_id: Option<String>,
+    #[serde(default, deserialize_with = "de_program")]
+    pub program: Option<String>,
+    #[serde(default)]
+    pub details: JwstDetails,
+    #[serde(default)]
+    pub file_type: Option<String>,
+    #[serde(default)]
+    pub thumbnail: Option<String>,
+    #[serde(default, alias = "url")]
+    pub location: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct JwstDetails {
+    #[serde(default)]
+    pub mission: Option<String>,
+    #[serde(default)]
+    pub instruments: Vec<JwstInstrument>,
+    #[serde(default)]
+    pub suffix: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JwstInstrument {
+    pub instrument: String,
+}
+
+// Нормализованная запись ленты: только наблюдения с картинкой
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwstImage {
+    pub id: String,
+    pub observation_id: Option<String>,
+    pub program: Option<String>,
+    pub suffix: Option<String>,
+    pub instruments: Vec<String>,
+    pub image_url: String,
+    pub thumbnail_url: Option<String>,
+    pub description: Option<String>,
+    pub caption: String,
+    #[serde(default = "Utc::now")]
+    pub fetched_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct JwstFilter {
+    pub program: Option<String>,
+    pub suffix: Option<String>,
+    pub instrument: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JwstFeed {
+    pub page: i64,
+    pub per_page: i64,
+    pub total: i64,
+    pub items: Vec<JwstImage>,
+}
+
+impl JwstObservation {
+    pub fn instruments(&self) -> Vec<String> {
+        self.details
+            .instruments
+            .iter()
+            .map(|i| i.instrument.trim().to_uppercase())
+            .filter(|i| !i.is_empty())
+            .collect()
+    }
+
+    // Полноразмерная картинка из location, иначе превью; FITS и прочее пропускаем
+    pub fn normalize(&self) -> Option<JwstImage> {
+        let image_url = [&self.location, &self.thumbnail]
+            .into_iter()
+            .flatten()
+            .find(|u| is_image_url(u))?
+            .clone();
+        let id = self
+            .id
+            .clone()
+            .or_else(|| {
+                self.observation_id
+                    .as_ref()
+                    .map(|o| format!("{}{}", o, self.details.suffix.as_deref().unwrap_or("")))
+            })
+            .unwrap_or_else(|| image_url.clone());
+        let instruments = self.instruments();
+
+        let mut caption = self.observation_id.clone().unwrap_or_else(|| id.clone());
+        caption.push_str(&format!(" · P{}", self.program.as_deref().unwrap_or("-")));
+        if let Some(suffix) = self.details.suffix.as_deref().filter(|s| !s.is_empty()) {
+            caption.push_str(&format!(" · {}", suffix));
+        }
+        if !instruments.is_empty() {
+            caption.push_str(&format!(" · {}", instruments.join("/")));
+        }
+
+        Some(JwstImage {
+            id,
+            observation_id: self.observation_id.clone(),
+            program: self.program.clone(),
+            suffix: self.details.suffix.clone(),
+            instruments,
+            image_url,
+            thumbnail_url: self.thumbnail.clone().filter(|u| is_image_url(u)),
+            description: self.details.description.clone(),
+            caption,
+            fetched_at: Utc::now(),
+        })
+    }
+}
+
+fn is_image_url(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or_default().to_lowercase();
+    (path.starts_with("http://") || path.starts_with("https://"))
+        && [".jpg", ".jpeg", ".png"].iter().any(|ext| path.ends_with(ext))
+}
+
+// program приходит то числом, то строкой
+fn de_program<'de, D: Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+    Ok(match Option::<Value>::deserialize(d)? {
+        Some(Value::String(s)) if !s.is_empty() => Some(s),
+        Some(Value::Number(n)) => Some(n.to_string()),
+        _ => None,
+    })
+}
@@ -1,8 +1,25 @@
+pub mod apod;
+pub mod astro;
+pub mod cme;
+pub mod ephemeris;
 pub mod error;
+pub mod flares;
+pub mod jwst;
 pub mod models;
+pub mod neo;
+pub mod space;
+pub mod space_weather;
 pub mod validation;
 
+pub use apod::*;
+pub use astro::*;
+pub use cme::*;
 pub use error::*;
+pub use flares::*;
+pub use jwst::*;
 pub use models::*;
+pub use neo::*;
+pub use space::*;
+pub use space_weather::{SpaceWeatherChain, SpaceWeatherEdge, SpaceWeatherEvent, SpaceWeatherNode};
 pub use validation::*;
 
@@ -1,11 +1,43 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::domain::{ApiError, ParseReport, SpacePayload};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Health {
     pub status: &'static str,
     pub now: DateTime<Utc>,
+    #[serde(default)]
+    pub upstreams: BTreeMap<String, CircuitStatus>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitStatus {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub opened_total: u64,
+    pub rejected_total: u64,
+    pub retry_in_seconds: Option<u64>,
+    pub last_error: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+// Результат условного запроса: Unchanged — апстрим ответил 304, сохранённая копия актуальна
+#[derive(Debug, Clone)]
+pub enum Fetched {
+    Fresh(Value),
+    Unchanged,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,21 +75,195 @@ pub struct OsdrItem {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpaceCacheEntry {
+    pub id: i64,
     pub source: String,
     pub fetched_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
     pub payload: Value,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceHistory {
+    pub source: String,
+    pub items: Vec<SpaceCacheEntry>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TypedSpaceEntry {
+    pub fetched_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub age_seconds: i64,
+    pub stale: bool,
+    #[serde(flatten)]
+    pub payload: SpacePayload,
+    pub report: ParseReport,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpaceSummary {
     pub apod: Value,
     pub neo: Value,
     pub flr: Value,
     pub cme: Value,
+    pub gst: Value,
+    pub sep: Value,
+    pub ips: Value,
+    pub hss: Value,
     pub spacex: Value,
     pub iss: Value,
     pub osdr_count: i64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefreshStatus {
+    Ok,
+    Error,
+    // Ручное обновление отклонено: источник обновлялся раньше минимального интервала
+    Throttled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceRefresh {
+    pub source: String,
+    pub status: RefreshStatus,
+    pub duration_ms: u64,
+    pub records: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshReport {
+    pub ok: usize,
+    pub failed: usize,
+    pub throttled: usize,
+    pub results: Vec<SourceRefresh>,
+}
+
+impl RefreshReport {
+    pub fn new(results: Vec<SourceRefresh>) -> Self {
+        let count = |status| results.iter().filter(|r| r.status == status).count();
+        Self {
+            ok: count(RefreshStatus::Ok),
+            failed: count(RefreshStatus::Error),
+            throttled: count(RefreshStatus::Throttled),
+            results,
+        }
+    }
+
+    // Ошибка, если не обновился ни один источник
+    pub fn ensure_any_ok(self) -> Result<Self, ApiError> {
+        if self.ok == 0 && self.failed > 0 {
+            return Err(ApiError::ExternalApi(self.error_summary()));
+        }
+        Ok(self)
+    }
+
+    // Ошибка, если не обновился хотя бы один источник
+    pub fn ensure_all_ok(self) -> Result<Self, ApiError> {
+        if self.failed > 0 {
+            return Err(ApiError::ExternalApi(self.error_summary()));
+        }
+        Ok(self)
+    }
+
+    fn error_summary(&self) -> String {
+        self.results
+            .iter()
+            .filter_map(|r| r.error.as_ref().map(|e| format!("{}: {}", r.source, e)))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PruneReport {
+    pub removed: BTreeMap<String, u64>,
+    pub kept_all: Vec<String>,
+    pub total_removed: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    OsdrSync,
+    SpaceRefresh,
+    CachePrune,
+}
+
+impl JobKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobKind::OsdrSync => "osdr_sync",
+            JobKind::SpaceRefresh => "space_refresh",
+            JobKind::CachePrune => "cache_prune",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "osdr_sync" => Some(JobKind::OsdrSync),
+            "space_refresh" => Some(JobKind::SpaceRefresh),
+            "cache_prune" => Some(JobKind::CachePrune),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Dead,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Done => "done",
+            JobStatus::Dead => "dead",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "queued" => Some(JobStatus::Queued),
+            "running" => Some(JobStatus::Running),
+            "done" => Some(JobStatus::Done),
+            "dead" => Some(JobStatus::Dead),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: i64,
+    pub kind: String,
+    pub payload: Value,
+    pub status: JobStatus,
+    pub priority: i32,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub idempotency_key: Option<String>,
+    pub last_error: Option<String>,
+    pub result: Option<Value>,
+    pub run_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewJob {
+    pub kind: JobKind,
+    pub payload: Value,
+    pub priority: i32,
+    pub idempotency_key: Option<String>,
+}
 
 
@@ -0,0 +1,101 @@
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::space::{NeoFeed, NeoLookup, NeoObject};
+
+// Одно сближение объекта с телом Солнечной системы — строка таблицы neo_approaches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NeoApproach {
+    pub neo_id: String,
+    pub name: String,
+    pub approach_date: NaiveDate,
+    pub approach_time: DateTime<Utc>,
+    pub orbiting_body: String,
+    pub miss_distance_km: f64,
+    pub miss_distance_ld: f64,
+    pub miss_distance_au: f64,
+    pub velocity_km_s: f64,
+    pub diameter_min_m: Option<f64>,
+    pub diameter_max_m: Option<f64>,
+    pub absolute_magnitude: Option<f64>,
+    pub hazardous: bool,
+    pub sentry: bool,
+    pub nasa_jpl_url: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NeoApproachFilter {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub hazardous: Option<bool>,
+    pub max_distance_ld: Option<f64>,
+    pub orbiting_body: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NeoApproaches {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub count: usize,
+    pub items: Vec<NeoApproach>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NeoRanking {
+    pub from: DateTime<Utc>,
+    pub to: NaiveDate,
+    // false — окно длиннее синхронизируемого, сближения после него известны не все
+    pub complete: bool,
+    pub closest: Vec<NeoApproach>,
+    pub largest: Vec<NeoApproach>,
+}
+
+// Запись из локального кэша lookup; stale — апстрим не ответил, отдана прежняя копия
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NeoObjectRecord {
+    pub id: String,
+    pub fetched_at: DateTime<Utc>,
+    pub stale: bool,
+    #[serde(flatten)]
+    pub object: NeoLookup,
+}
+
+impl NeoObject {
+    pub fn approaches(&self) -> Vec<NeoApproach> {
+        let meters = self.estimated_diameter.as_ref().and_then(|d| d.meters.as_ref());
+        self.close_approach_data
+            .iter()
+            .map(|ca| NeoApproach {
+                neo_id: self.id.clone(),
+                name: self.name.clone(),
+                approach_date: ca.close_approach_date,
+                // Точное время есть не у всех записей — тогда полночь UTC даты сближения
+                approach_time: ca
+                    .epoch_date_close_approach
+                    .and_then(DateTime::from_timestamp_millis)
+                    .unwrap_or_else(|| ca.close_approach_date.and_time(NaiveTime::MIN).and_utc()),
+                orbiting_body: ca.orbiting_body.clone().unwrap_or_else(|| "Earth".to_string()),
+                miss_distance_km: ca.miss_distance.kilometers,
+                miss_distance_ld: ca.miss_distance.lunar,
+                miss_distance_au: ca.miss_distance.astronomical,
+                velocity_km_s: ca.relative_velocity.kilometers_per_second,
+                diameter_min_m: meters.map(|m| m.estimated_diameter_min),
+                diameter_max_m: meters.map(|m| m.estimated_diameter_max),
+                absolute_magnitude: self.absolute_magnitude_h,
+                hazardous: self.is_potentially_hazardous_asteroid,
+                sentry: self.is_sentry_object,
+                nasa_jpl_url: self.nasa_jpl_url.clone(),
+            })
+            .collect()
+    }
+}
+
+impl NeoFeed {
+    pub fn approaches(&self) -> Vec<NeoApproach> {
+        self.near_earth_objects
+            .values()
+            .flatten()
+            .flat_map(|o| o.approaches())
+            .collect()
+    }
+}
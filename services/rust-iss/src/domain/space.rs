@@ -0,0 +1,705 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+// Типизированные модели ответов внешних API.
+// Десериализуются из имён полей апстрима, наружу отдаются в snake_case —
+// это контракт /space/{src}/typed, не зависящий от изменений апстрима.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Apod {
+    pub date: NaiveDate,
+    pub title: String,
+    pub explanation: String,
+    pub media_type: String,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub hdurl: Option<String>,
+    #[serde(default)]
+    pub thumbnail_url: Option<String>,
+    #[serde(default)]
+    pub copyright: Option<String>,
+    #[serde(default)]
+    pub service_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NeoFeed {
+    pub element_count: u32,
+    pub near_earth_objects: BTreeMap<NaiveDate, Vec<NeoObject>>,
+    #[serde(default)]
+    pub links: Option<NeoLinks>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NeoLinks {
+    #[serde(default)]
+    pub next: Option<String>,
+    #[serde(default)]
+    pub prev: Option<String>,
+    #[serde(default, rename = "self")]
+    pub self_link: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NeoObject {
+    pub id: String,
+    #[serde(default)]
+    pub neo_reference_id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub nasa_jpl_url: Option<String>,
+    #[serde(default)]
+    pub absolute_magnitude_h: Option<f64>,
+    #[serde(default)]
+    pub estimated_diameter: Option<EstimatedDiameter>,
+    pub is_potentially_hazardous_asteroid: bool,
+    #[serde(default)]
+    pub close_approach_data: Vec<CloseApproach>,
+    #[serde(default)]
+    pub is_sentry_object: bool,
+    #[serde(default)]
+    pub sentry_data: Option<String>,
+    #[serde(default)]
+    pub links: Option<NeoLinks>,
+}
+
+// Ответ NeoWs lookup: объект целиком, со всеми сближениями и орбитой
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NeoLookup {
+    #[serde(flatten)]
+    pub object: NeoObject,
+    #[serde(default)]
+    pub designation: Option<String>,
+    #[serde(default)]
+    pub name_limited: Option<String>,
+    #[serde(default)]
+    pub orbital_data: Option<OrbitalData>,
+}
+
+// Элементы орбиты; NeoWs отдаёт числа строками
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrbitalData {
+    #[serde(default)]
+    pub orbit_id: Option<String>,
+    #[serde(default)]
+    pub orbit_determination_date: Option<String>,
+    #[serde(default)]
+    pub first_observation_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub last_observation_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub data_arc_in_days: Option<i64>,
+    #[serde(default)]
+    pub observations_used: Option<i64>,
+    #[serde(default)]
+    pub orbit_uncertainty: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub minimum_orbit_intersection: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub jupiter_tisserand_invariant: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub epoch_osculation: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub eccentricity: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub semi_major_axis: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub inclination: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub ascending_node_longitude: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub orbital_period: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub perihelion_distance: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub perihelion_argument: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub aphelion_distance: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub perihelion_time: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub mean_anomaly: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub mean_motion: Option<f64>,
+    #[serde(default)]
+    pub equinox: Option<String>,
+    #[serde(default)]
+    pub orbit_class: Option<OrbitClass>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrbitClass {
+    #[serde(default)]
+    pub orbit_class_type: Option<String>,
+    #[serde(default)]
+    pub orbit_class_description: Option<String>,
+    #[serde(default)]
+    pub orbit_class_range: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EstimatedDiameter {
+    #[serde(default)]
+    pub kilometers: Option<DiameterRange>,
+    #[serde(default)]
+    pub meters: Option<DiameterRange>,
+    #[serde(default)]
+    pub miles: Option<DiameterRange>,
+    #[serde(default)]
+    pub feet: Option<DiameterRange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiameterRange {
+    pub estimated_diameter_min: f64,
+    pub estimated_diameter_max: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloseApproach {
+    pub close_approach_date: NaiveDate,
+    #[serde(default)]
+    pub close_approach_date_full: Option<String>,
+    #[serde(default)]
+    pub epoch_date_close_approach: Option<i64>,
+    pub relative_velocity: RelativeVelocity,
+    pub miss_distance: MissDistance,
+    #[serde(default)]
+    pub orbiting_body: Option<String>,
+}
+
+// NeoWs отдаёт числа строками
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelativeVelocity {
+    #[serde(deserialize_with = "de_f64")]
+    pub kilometers_per_second: f64,
+    #[serde(deserialize_with = "de_f64")]
+    pub kilometers_per_hour: f64,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub miles_per_hour: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissDistance {
+    #[serde(deserialize_with = "de_f64")]
+    pub astronomical: f64,
+    #[serde(deserialize_with = "de_f64")]
+    pub lunar: f64,
+    #[serde(deserialize_with = "de_f64")]
+    pub kilometers: f64,
+    #[serde(default, deserialize_with = "de_opt_f64")]
+    pub miles: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DonkiInstrument {
+    #[serde(rename(deserialize = "displayName"))]
+    pub display_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DonkiLinkedEvent {
+    #[serde(rename(deserialize = "activityID"))]
+    pub activity_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct DonkiFlare {
+    #[serde(rename(deserialize = "flrID"))]
+    pub flr_id: String,
+    #[serde(default)]
+    pub catalog: Option<String>,
+    #[serde(default, deserialize_with = "de_null_vec")]
+    pub instruments: Vec<DonkiInstrument>,
+    #[serde(deserialize_with = "de_donki_time")]
+    pub begin_time: DateTime<Utc>,
+    #[serde(default, deserialize_with = "de_opt_donki_time")]
+    pub peak_time: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "de_opt_donki_time")]
+    pub end_time: Option<DateTime<Utc>>,
+    pub class_type: String,
+    #[serde(default)]
+    pub source_location: Option<String>,
+    #[serde(default)]
+    pub active_region_num: Option<i64>,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_donki_time")]
+    pub submission_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub version_id: Option<i64>,
+    #[serde(default)]
+    pub link: Option<String>,
+    #[serde(default, deserialize_with = "de_null_vec")]
+    pub linked_events: Vec<DonkiLinkedEvent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct DonkiCme {
+    #[serde(rename(deserialize = "activityID"))]
+    pub activity_id: String,
+    #[serde(default)]
+    pub catalog: Option<String>,
+    #[serde(deserialize_with = "de_donki_time")]
+    pub start_time: DateTime<Utc>,
+    #[serde(default)]
+    pub source_location: Option<String>,
+    #[serde(default)]
+    pub active_region_num: Option<i64>,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_donki_time")]
+    pub submission_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub version_id: Option<i64>,
+    #[serde(default)]
+    pub link: Option<String>,
+    #[serde(default, deserialize_with = "de_null_vec")]
+    pub instruments: Vec<DonkiInstrument>,
+    #[serde(default, deserialize_with = "de_null_vec")]
+    pub cme_analyses: Vec<CmeAnalysis>,
+    #[serde(default, deserialize_with = "de_null_vec")]
+    pub linked_events: Vec<DonkiLinkedEvent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct CmeAnalysis {
+    #[serde(default, rename(deserialize = "time21_5"), deserialize_with = "de_opt_donki_time")]
+    pub time21_5: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub latitude: Option<f64>,
+    #[serde(default)]
+    pub longitude: Option<f64>,
+    #[serde(default)]
+    pub half_angle: Option<f64>,
+    #[serde(default)]
+    pub speed: Option<f64>,
+    #[serde(default, rename(deserialize = "type"))]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub is_most_accurate: bool,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub level_of_data: Option<i64>,
+    #[serde(default)]
+    pub link: Option<String>,
+    #[serde(default, deserialize_with = "de_null_vec")]
+    pub enlil_list: Vec<EnlilRun>,
+}
+
+// Прогон модели WSA-ENLIL. Если модель предсказала удар по Земле,
+// заполнено estimatedShockArrivalTime; kp_* — оценки Kp для разных углов Bz
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct EnlilRun {
+    #[serde(default, deserialize_with = "de_opt_donki_time")]
+    pub model_completion_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub au: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_donki_time")]
+    pub estimated_shock_arrival_time: Option<DateTime<Utc>>,
+    // Часы
+    #[serde(default)]
+    pub estimated_duration: Option<f64>,
+    #[serde(default, rename(deserialize = "rmin_re"))]
+    pub rmin_re: Option<f64>,
+    #[serde(default, rename(deserialize = "kp_18"))]
+    pub kp_18: Option<f64>,
+    #[serde(default, rename(deserialize = "kp_90"))]
+    pub kp_90: Option<f64>,
+    #[serde(default, rename(deserialize = "kp_135"))]
+    pub kp_135: Option<f64>,
+    #[serde(default, rename(deserialize = "kp_180"))]
+    pub kp_180: Option<f64>,
+    #[serde(default, rename(deserialize = "isEarthGB"))]
+    pub is_earth_gb: Option<bool>,
+    #[serde(default)]
+    pub link: Option<String>,
+    #[serde(default, deserialize_with = "de_null_vec")]
+    pub impact_list: Vec<EnlilImpact>,
+    #[serde(default, rename(deserialize = "cmeIDs"), deserialize_with = "de_null_vec")]
+    pub cme_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct EnlilImpact {
+    #[serde(default)]
+    pub is_glancing_blow: bool,
+    pub location: String,
+    #[serde(deserialize_with = "de_donki_time")]
+    pub arrival_time: DateTime<Utc>,
+}
+
+// Геомагнитная буря: Kp-индексы за всё время бури
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct DonkiGst {
+    #[serde(rename(deserialize = "gstID"))]
+    pub gst_id: String,
+    #[serde(deserialize_with = "de_donki_time")]
+    pub start_time: DateTime<Utc>,
+    #[serde(default, deserialize_with = "de_null_vec")]
+    pub all_kp_index: Vec<KpIndex>,
+    #[serde(default, deserialize_with = "de_opt_donki_time")]
+    pub submission_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub version_id: Option<i64>,
+    #[serde(default)]
+    pub link: Option<String>,
+    #[serde(default, deserialize_with = "de_null_vec")]
+    pub linked_events: Vec<DonkiLinkedEvent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct KpIndex {
+    #[serde(deserialize_with = "de_donki_time")]
+    pub observed_time: DateTime<Utc>,
+    #[serde(deserialize_with = "de_f64")]
+    pub kp_index: f64,
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+// Солнечные энергичные частицы
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct DonkiSep {
+    #[serde(rename(deserialize = "sepID"))]
+    pub sep_id: String,
+    #[serde(deserialize_with = "de_donki_time")]
+    pub event_time: DateTime<Utc>,
+    #[serde(default, deserialize_with = "de_null_vec")]
+    pub instruments: Vec<DonkiInstrument>,
+    #[serde(default, deserialize_with = "de_opt_donki_time")]
+    pub submission_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub version_id: Option<i64>,
+    #[serde(default)]
+    pub link: Option<String>,
+    #[serde(default, deserialize_with = "de_null_vec")]
+    pub linked_events: Vec<DonkiLinkedEvent>,
+}
+
+// Межпланетная ударная волна; location — где зарегистрирована (Earth, STEREO A, ...)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct DonkiIps {
+    #[serde(rename(deserialize = "activityID"))]
+    pub activity_id: String,
+    #[serde(default)]
+    pub catalog: Option<String>,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(deserialize_with = "de_donki_time")]
+    pub event_time: DateTime<Utc>,
+    #[serde(default, deserialize_with = "de_null_vec")]
+    pub instruments: Vec<DonkiInstrument>,
+    #[serde(default, deserialize_with = "de_opt_donki_time")]
+    pub submission_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub version_id: Option<i64>,
+    #[serde(default)]
+    pub link: Option<String>,
+    #[serde(default, deserialize_with = "de_null_vec")]
+    pub linked_events: Vec<DonkiLinkedEvent>,
+}
+
+// Высокоскоростной поток солнечного ветра
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct DonkiHss {
+    #[serde(rename(deserialize = "hssID"))]
+    pub hss_id: String,
+    #[serde(deserialize_with = "de_donki_time")]
+    pub event_time: DateTime<Utc>,
+    #[serde(default, deserialize_with = "de_null_vec")]
+    pub instruments: Vec<DonkiInstrument>,
+    #[serde(default, deserialize_with = "de_opt_donki_time")]
+    pub submission_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub version_id: Option<i64>,
+    #[serde(default)]
+    pub link: Option<String>,
+    #[serde(default, deserialize_with = "de_null_vec")]
+    pub linked_events: Vec<DonkiLinkedEvent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceXLaunch {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub flight_number: Option<i64>,
+    pub date_utc: DateTime<Utc>,
+    #[serde(default)]
+    pub date_unix: Option<i64>,
+    #[serde(default)]
+    pub date_local: Option<String>,
+    #[serde(default)]
+    pub date_precision: Option<String>,
+    #[serde(default)]
+    pub upcoming: bool,
+    #[serde(default)]
+    pub tbd: Option<bool>,
+    #[serde(default)]
+    pub net: Option<bool>,
+    #[serde(default)]
+    pub window: Option<i64>,
+    #[serde(default)]
+    pub success: Option<bool>,
+    #[serde(default)]
+    pub details: Option<String>,
+    #[serde(default)]
+    pub rocket: Option<String>,
+    #[serde(default)]
+    pub launchpad: Option<String>,
+    #[serde(default)]
+    pub payloads: Vec<String>,
+    #[serde(default)]
+    pub capsules: Vec<String>,
+    #[serde(default)]
+    pub ships: Vec<String>,
+    #[serde(default)]
+    pub crew: Vec<Value>,
+    #[serde(default)]
+    pub cores: Vec<Value>,
+    #[serde(default)]
+    pub failures: Vec<Value>,
+    #[serde(default)]
+    pub fairings: Option<Value>,
+    #[serde(default)]
+    pub links: Option<SpaceXLinks>,
+    #[serde(default)]
+    pub static_fire_date_utc: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub static_fire_date_unix: Option<i64>,
+    #[serde(default)]
+    pub auto_update: Option<bool>,
+    #[serde(default)]
+    pub launch_library_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceXLinks {
+    #[serde(default)]
+    pub patch: Option<SpaceXPatch>,
+    #[serde(default)]
+    pub webcast: Option<String>,
+    #[serde(default)]
+    pub youtube_id: Option<String>,
+    #[serde(default)]
+    pub article: Option<String>,
+    #[serde(default)]
+    pub wikipedia: Option<String>,
+    #[serde(default)]
+    pub presskit: Option<String>,
+    #[serde(default)]
+    pub reddit: Option<Value>,
+    #[serde(default)]
+    pub flickr: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceXPatch {
+    #[serde(default)]
+    pub small: Option<String>,
+    #[serde(default)]
+    pub large: Option<String>,
+}
+
+// Итог разбора: неизвестные поля и отброшенные элементы не ломают разбор,
+// но попадают в отчёт, чтобы изменения апстрима были видны
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParseReport {
+    pub unknown_fields: Vec<String>,
+    pub missing_fields: Vec<String>,
+    pub rejected: usize,
+}
+
+impl ParseReport {
+    pub fn is_clean(&self) -> bool {
+        self.unknown_fields.is_empty() && self.missing_fields.is_empty() && self.rejected == 0
+    }
+
+    fn merge(&mut self, prefix: &str, other: ParseReport) {
+        self.unknown_fields
+            .extend(other.unknown_fields.into_iter().map(|f| format!("{}{}", prefix, f)));
+        self.missing_fields
+            .extend(other.missing_fields.into_iter().map(|f| format!("{}{}", prefix, f)));
+        self.rejected += other.rejected;
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "source", content = "data", rename_all = "snake_case")]
+pub enum SpacePayload {
+    Apod(Apod),
+    Neo(NeoFeed),
+    Flr(Vec<DonkiFlare>),
+    Cme(Vec<DonkiCme>),
+    Gst(Vec<DonkiGst>),
+    Sep(Vec<DonkiSep>),
+    Ips(Vec<DonkiIps>),
+    Hss(Vec<DonkiHss>),
+    Spacex(Box<SpaceXLaunch>),
+}
+
+impl SpacePayload {
+    pub fn parse(source: &str, payload: &Value) -> Result<(Self, ParseReport), String> {
+        match source {
+            "apod" => parse_one(payload).map(|(v, r)| (SpacePayload::Apod(v), r)),
+            "neo" => parse_one(payload).map(|(v, r)| (SpacePayload::Neo(v), r)),
+            "flr" => parse_list(payload).map(|(v, r)| (SpacePayload::Flr(v), r)),
+            "cme" => parse_list(payload).map(|(v, r)| (SpacePayload::Cme(v), r)),
+            "gst" => parse_list(payload).map(|(v, r)| (SpacePayload::Gst(v), r)),
+            "sep" => parse_list(payload).map(|(v, r)| (SpacePayload::Sep(v), r)),
+            "ips" => parse_list(payload).map(|(v, r)| (SpacePayload::Ips(v), r)),
+            "hss" => parse_list(payload).map(|(v, r)| (SpacePayload::Hss(v), r)),
+            "spacex" => parse_one(payload).map(|(v, r)| (SpacePayload::Spacex(Box::new(v)), r)),
+            _ => Err(format!("no typed model for source {}", source)),
+        }
+    }
+}
+
+// Строгий разбор одного объекта: отсутствие обязательного поля — ошибка,
+// неизвестные поля только попадают в отчёт
+pub fn parse_one<T: DeserializeOwned>(payload: &Value) -> Result<(T, ParseReport), String> {
+    let mut report = ParseReport::default();
+    let mut track = serde_path_to_error::Track::new();
+    let de = serde_path_to_error::Deserializer::new(payload, &mut track);
+
+    let parsed = serde_ignored::deserialize(de, |path| {
+        report.unknown_fields.push(path.to_string());
+    });
+
+    match parsed {
+        Ok(v) => Ok((v, report)),
+        Err(e) => {
+            let path = track.path().to_string();
+            Err(match missing_field(&e.to_string()) {
+                Some(field) => format!("missing field {}", join_path(&path, &field)),
+                None => format!("{} at {}", e, path),
+            })
+        }
+    }
+}
+
+// Терпимый разбор списка: битый элемент отбрасывается и учитывается в отчёте
+pub fn parse_list<T: DeserializeOwned>(payload: &Value) -> Result<(Vec<T>, ParseReport), String> {
+    let items = payload
+        .as_array()
+        .ok_or_else(|| "expected array".to_string())?;
+
+    let mut report = ParseReport::default();
+    let mut out = Vec::with_capacity(items.len());
+    for (i, item) in items.iter().enumerate() {
+        match parse_one::<T>(item) {
+            Ok((v, r)) => {
+                report.merge(&format!("[{}].", i), r);
+                out.push(v);
+            }
+            Err(e) => {
+                report.rejected += 1;
+                if let Some(field) = e.strip_prefix("missing field ") {
+                    report.missing_fields.push(format!("[{}].{}", i, field));
+                }
+            }
+        }
+    }
+
+    // Схлопываем повторы вида [0].x, [1].x — важен сам факт поля, а не номер элемента
+    for list in [&mut report.unknown_fields, &mut report.missing_fields] {
+        for f in list.iter_mut() {
+            if let Some(pos) = f.find("].") {
+                *f = format!("[].{}", &f[pos + 2..]);
+            }
+        }
+        list.sort();
+        list.dedup();
+    }
+
+    if out.is_empty() && !items.is_empty() {
+        return Err(format!("all {} items rejected", items.len()));
+    }
+    Ok((out, report))
+}
+
+fn missing_field(msg: &str) -> Option<String> {
+    let rest = msg.strip_prefix("missing field `")?;
+    rest.find('`').map(|end| rest[..end].to_string())
+}
+
+fn join_path(path: &str, field: &str) -> String {
+    if path.is_empty() || path == "." {
+        field.to_string()
+    } else {
+        format!("{}.{}", path, field)
+    }
+}
+
+fn de_f64<'de, D: Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+    match Value::deserialize(d)? {
+        Value::Number(n) => n
+            .as_f64()
+            .ok_or_else(|| serde::de::Error::custom("invalid number")),
+        Value::String(s) => s.trim().parse().map_err(serde::de::Error::custom),
+        other => Err(serde::de::Error::custom(format!("expected number, got {}", other))),
+    }
+}
+
+fn de_opt_f64<'de, D: Deserializer<'de>>(d: D) -> Result<Option<f64>, D::Error> {
+    match Value::deserialize(d)? {
+        Value::Null => Ok(None),
+        Value::Number(n) => Ok(n.as_f64()),
+        Value::String(s) if s.trim().is_empty() => Ok(None),
+        Value::String(s) => s.trim().parse().map(Some).map_err(serde::de::Error::custom),
+        other => Err(serde::de::Error::custom(format!("expected number, got {}", other))),
+    }
+}
+
+// DONKI пишет время без секунд: 2024-05-01T12:34Z
+pub fn parse_donki_time(s: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    let trimmed = s.trim_end_matches('Z');
+    ["%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(trimmed, f).ok())
+        .map(|ndt| ndt.and_utc())
+}
+
+fn de_donki_time<'de, D: Deserializer<'de>>(d: D) -> Result<DateTime<Utc>, D::Error> {
+    let s = String::deserialize(d)?;
+    parse_donki_time(&s).ok_or_else(|| serde::de::Error::custom(format!("invalid time {}", s)))
+}
+
+fn de_opt_donki_time<'de, D: Deserializer<'de>>(d: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+    match Option::<String>::deserialize(d)? {
+        None => Ok(None),
+        Some(s) if s.is_empty() => Ok(None),
+        Some(s) => parse_donki_time(&s)
+            .map(Some)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid time {}", s))),
+    }
+}
+
+// DONKI отдаёт null вместо пустого списка
+fn de_null_vec<'de, D, T>(d: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(Option::<Vec<T>>::deserialize(d)?.unwrap_or_default())
+}
@@ -0,0 +1,197 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::domain::space::{DonkiLinkedEvent, SpacePayload};
+
+// Нормализованное событие DONKI. Идентификатор у всех лент одного вида:
+// 2024-05-10T06:27:00-FLR-001 — время начала и тип события зашиты в нём
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceWeatherEvent {
+    pub activity_id: String,
+    pub kind: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: Option<DateTime<Utc>>,
+    // Короткое описание для списка: класс вспышки, скорость CME, Kp бури
+    pub summary: Option<String>,
+    pub link: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub linked: Vec<String>,
+    pub details: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceWeatherEdge {
+    pub cause: String,
+    pub effect: String,
+}
+
+// Узел цепочки. Событие, на которое есть ссылка, но которого нет в таблице
+// (например, MPC или RBE — их ленты не опрашиваются), отдаётся с known = false
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceWeatherNode {
+    pub activity_id: String,
+    pub kind: String,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub summary: Option<String>,
+    pub link: Option<String>,
+    pub known: bool,
+    // Число связей до корня цепочки
+    pub depth: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceWeatherChain {
+    pub event: SpaceWeatherNode,
+    pub causes: Vec<SpaceWeatherNode>,
+    pub effects: Vec<SpaceWeatherNode>,
+    pub edges: Vec<SpaceWeatherEdge>,
+}
+
+impl SpaceWeatherNode {
+    pub fn from_event(e: &SpaceWeatherEvent, depth: i32) -> Self {
+        Self {
+            activity_id: e.activity_id.clone(),
+            kind: e.kind.clone(),
+            start_time: Some(e.start_time),
+            end_time: e.end_time,
+            summary: e.summary.clone(),
+            link: e.link.clone(),
+            known: true,
+            depth,
+        }
+    }
+
+    pub fn unknown(activity_id: &str, depth: i32) -> Self {
+        Self {
+            activity_id: activity_id.to_string(),
+            kind: activity_kind(activity_id).unwrap_or_else(|| "unknown".to_string()),
+            start_time: activity_time(activity_id),
+            end_time: None,
+            summary: None,
+            link: None,
+            known: false,
+            depth,
+        }
+    }
+}
+
+pub fn activity_kind(activity_id: &str) -> Option<String> {
+    let mut parts = activity_id.rsplitn(3, '-');
+    let _seq = parts.next()?;
+    let kind = parts.next()?;
+    (!kind.is_empty() && kind.chars().all(|c| c.is_ascii_alphabetic())).then(|| kind.to_ascii_lowercase())
+}
+
+pub fn activity_time(activity_id: &str) -> Option<DateTime<Utc>> {
+    let stamp = activity_id.get(..19)?;
+    NaiveDateTime::parse_from_str(stamp, "%Y-%m-%dT%H:%M:%S")
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+// Порядок в физической цепочке: вспышка → CME → частицы → ударная волна → буря
+fn causal_rank(kind: &str) -> u8 {
+    match kind {
+        "flr" => 0,
+        "cme" => 1,
+        "sep" => 2,
+        "ips" | "hss" => 3,
+        "mpc" => 4,
+        "gst" => 5,
+        "rbe" => 6,
+        _ => 7,
+    }
+}
+
+// DONKI ссылается в обе стороны и без направления; направление связи
+// задаёт тип события, а для событий одного типа — время начала
+pub fn orient(a: &str, b: &str) -> SpaceWeatherEdge {
+    let key = |id: &str| {
+        let rank = activity_kind(id).map(|k| causal_rank(&k)).unwrap_or(u8::MAX);
+        (rank, activity_time(id), id.to_string())
+    };
+    let (cause, effect) = if key(a) <= key(b) { (a, b) } else { (b, a) };
+    SpaceWeatherEdge {
+        cause: cause.to_string(),
+        effect: effect.to_string(),
+    }
+}
+
+fn event<T: Serialize>(
+    activity_id: &str,
+    kind: &str,
+    start_time: DateTime<Utc>,
+    link: &Option<String>,
+    linked: &[DonkiLinkedEvent],
+    details: &T,
+) -> SpaceWeatherEvent {
+    SpaceWeatherEvent {
+        activity_id: activity_id.to_string(),
+        kind: kind.to_string(),
+        start_time,
+        end_time: None,
+        summary: None,
+        link: link.clone(),
+        linked: linked.iter().map(|l| l.activity_id.clone()).collect(),
+        details: serde_json::to_value(details).unwrap_or(Value::Null),
+    }
+}
+
+impl SpacePayload {
+    // События DONKI из типизированного снимка; для остальных источников — пусто
+    pub fn weather_events(&self) -> Vec<SpaceWeatherEvent> {
+        match self {
+            SpacePayload::Flr(items) => items
+                .iter()
+                .map(|f| SpaceWeatherEvent {
+                    end_time: f.end_time,
+                    summary: Some(f.class_type.clone()).filter(|c| !c.is_empty()),
+                    ..event(&f.flr_id, "flr", f.begin_time, &f.link, &f.linked_events, f)
+                })
+                .collect(),
+            SpacePayload::Cme(items) => items
+                .iter()
+                .map(|c| {
+                    let speed = c
+                        .cme_analyses
+                        .iter()
+                        .find(|a| a.is_most_accurate)
+                        .or(c.cme_analyses.first())
+                        .and_then(|a| a.speed);
+                    SpaceWeatherEvent {
+                        summary: speed.map(|s| format!("{:.0} km/s", s)),
+                        ..event(&c.activity_id, "cme", c.start_time, &c.link, &c.linked_events, c)
+                    }
+                })
+                .collect(),
+            SpacePayload::Gst(items) => items
+                .iter()
+                .map(|g| {
+                    let kp = g.all_kp_index.iter().map(|k| k.kp_index).reduce(f64::max);
+                    SpaceWeatherEvent {
+                        summary: kp.map(|k| format!("Kp {}", k)),
+                        ..event(&g.gst_id, "gst", g.start_time, &g.link, &g.linked_events, g)
+                    }
+                })
+                .collect(),
+            SpacePayload::Sep(items) => items
+                .iter()
+                .map(|s| event(&s.sep_id, "sep", s.event_time, &s.link, &s.linked_events, s))
+                .collect(),
+            SpacePayload::Ips(items) => items
+                .iter()
+                .map(|i| SpaceWeatherEvent {
+                    summary: i.location.clone(),
+                    ..event(&i.activity_id, "ips", i.event_time, &i.link, &i.linked_events, i)
+                })
+                .collect(),
+            SpacePayload::Hss(items) => items
+                .iter()
+                .map(|h| event(&h.hss_id, "hss", h.event_time, &h.link, &h.linked_events, h))
+                .collect(),
+            SpacePayload::Apod(_) | SpacePayload::Neo(_) | SpacePayload::Spacex(_) => Vec::new(),
+        }
+    }
+}
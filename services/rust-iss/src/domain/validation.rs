@@ -1,5 +1,7 @@
 use serde_json::Value;
 
+use crate::domain::space::{ParseReport, SpacePayload};
+
 pub fn validate_iss_payload(payload: &Value) -> Result<(), String> {
     if !payload.is_object() {
         return Err("invalid_format".to_string());
@@ -33,5 +35,10 @@ pub fn validate_space_cache_entry(source: &str, payload: &Value) -> Result<(), S
     Ok(())
 }
 
+// Типизированный разбор ответа источника; неизвестные поля не считаются ошибкой
+pub fn validate_space_payload(source: &str, payload: &Value) -> Result<ParseReport, String> {
+    validate_space_cache_entry(source, payload)?;
+    SpacePayload::parse(source, payload).map(|(_, report)| report)
+}
 
 
@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use chrono::{Days, NaiveDate, Utc};
+
+use crate::domain::{apod_first_date, ApiError, Apod, ApodFilter, ApodPage};
+use crate::AppState;
+
+fn parse_date(key: &str, s: &str) -> Result<NaiveDate, ApiError> {
+    NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").map_err(|_| ApiError::Validation(format!("invalid {}: {}", key, s)))
+}
+
+// /apod/{date} — выпуск за дату YYYY-MM-DD
+pub async fn apod_by_date(
+    Path(date): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<Apod>, ApiError> {
+    let apod = state.apod_service.by_date(parse_date("date", &date)?).await?;
+    Ok(Json(apod))
+}
+
+// /apod?from=&to=&media_type=image|video|other&limit= — архив за окно, по умолчанию 30 дней.
+// &random=N — N случайных выпусков; без from выбор идёт по всему архиву
+pub async fn apod_list(
+    Query(q): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> Result<Json<ApodPage>, ApiError> {
+    let text = |k: &str| q.get(k).map(|s| s.trim()).filter(|s| !s.is_empty());
+    let random = text("random")
+        .map(|s| s.parse::<i64>().map_err(|_| ApiError::Validation(format!("invalid random: {}", s))))
+        .transpose()?;
+    let limit = text("limit")
+        .map(|s| s.parse::<i64>().map_err(|_| ApiError::Validation(format!("invalid limit: {}", s))))
+        .transpose()?
+        .unwrap_or(100);
+
+    let to = text("to").map(|s| parse_date("to", s)).transpose()?.unwrap_or_else(|| Utc::now().date_naive());
+    let from = match text("from") {
+        Some(s) => parse_date("from", s)?,
+        None if random.is_some() => apod_first_date(),
+        None => to - Days::new(30),
+    };
+    let filter = ApodFilter {
+        from,
+        to,
+        media_type: text("media_type").map(str::to_lowercase),
+    };
+
+    let page = state.apod_service.browse(filter, random, limit).await?;
+    Ok(Json(page))
+}
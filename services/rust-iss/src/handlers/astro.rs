@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+
+use axum::extract::{Query, State};
+use axum::Json;
+use chrono::NaiveDate;
+
+use crate::domain::ephemeris::SkyReport;
+use crate::domain::{ApiError, AstroEvents};
+use crate::AppState;
+
+fn parse_param<T: std::str::FromStr>(q: &HashMap<String, String>, key: &str, default: T) -> Result<T, ApiError> {
+    match q.get(key).map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        Some(s) => s
+            .parse::<T>()
+            .map_err(|_| ApiError::Validation(format!("invalid {}: {}", key, s))),
+        None => Ok(default),
+    }
+}
+
+// /astro/events?lat=&lon=&days= — по умолчанию Москва на неделю
+pub async fn astro_events(
+    Query(q): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> Result<Json<AstroEvents>, ApiError> {
+    let lat = parse_param(&q, "lat", 55.7558)?;
+    let lon = parse_param(&q, "lon", 37.6176)?;
+    let days = parse_param(&q, "days", 7u64)?;
+
+    let events = state.astro_service.events(lat, lon, days).await?;
+    Ok(Json(events))
+}
+
+// /astro/sky?lat=&lon=&date=YYYY-MM-DD — Солнце и Луна, считается локально
+pub async fn astro_sky(
+    Query(q): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> Result<Json<SkyReport>, ApiError> {
+    let lat = parse_param(&q, "lat", 55.7558)?;
+    let lon = parse_param(&q, "lon", 37.6176)?;
+    let date = match q.get("date").map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        Some(s) => Some(
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .map_err(|_| ApiError::Validation(format!("invalid date: {}", s)))?,
+        ),
+        None => None,
+    };
+
+    let sky = state.astro_service.sky(lat, lon, date)?;
+    Ok(Json(sky))
+}
@@ -1,14 +1,21 @@
-use axum::Json;
+use axum::{extract::State, Json};
 use chrono::Utc;
 
-use crate::domain::Health;
+use crate::domain::{CircuitState, Health};
+use crate::AppState;
+
+pub async fn health(State(state): State<AppState>) -> Json<Health> {
+    let upstreams = state.http.circuit_status();
+    // Сервис жив, но часть апстримов отключена breaker'ом
+    let status = if upstreams.values().any(|c| c.state != CircuitState::Closed) {
+        "degraded"
+    } else {
+        "ok"
+    };
 
-pub async fn health() -> Json<Health> {
     Json(Health {
-        status: "ok",
+        status,
         now: Utc::now(),
+        upstreams,
     })
 }
-
-
-
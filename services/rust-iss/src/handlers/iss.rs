@@ -19,7 +19,7 @@ pub async fn last_iss(State(state): State<AppState>) -> Result<Json<Value>, ApiE
 pub async fn trigger_iss(State(state): State<AppState>) -> Result<Json<Value>, ApiError> {
     state
         .iss_service
-        .fetch_and_store(&state.config.where_iss_url)
+        .trigger_fetch(&state.config.where_iss_url)
         .await?;
     last_iss(State(state)).await
 }
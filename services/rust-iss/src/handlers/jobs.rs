@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+
+use axum::extract::{Path, Query, State};
+use axum::Json;
+
+use crate::domain::{ApiError, Job, JobStatus};
+use crate::AppState;
+
+pub async fn job_get(
+    Path(id): Path<i64>,
+    State(state): State<AppState>,
+) -> Result<Json<Job>, ApiError> {
+    let job = state.job_service.get(id).await?;
+    Ok(Json(job))
+}
+
+pub async fn job_list(
+    Query(q): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Job>>, ApiError> {
+    let status = match q.get("status") {
+        Some(s) => Some(
+            JobStatus::parse(s)
+                .ok_or_else(|| ApiError::Validation(format!("unknown job status: {}", s)))?,
+        ),
+        None => None,
+    };
+    let limit = q
+        .get("limit")
+        .and_then(|s| s.parse::<i64>().ok())
+        .unwrap_or(50);
+
+    let jobs = state.job_service.list(status, limit).await?;
+    Ok(Json(jobs))
+}
@@ -0,0 +1,37 @@
+use std::collections::HashMap;
+
+use axum::extract::{Query, State};
+use axum::Json;
+
+use crate::domain::{ApiError, JwstFeed, JwstFilter};
+use crate::AppState;
+
+// /jwst/feed?program=&suffix=&instrument=&page=&perPage=
+pub async fn jwst_feed(
+    Query(q): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> Result<Json<JwstFeed>, ApiError> {
+    let text = |k: &str| q.get(k).map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    let number = |k: &str, default: i64| -> Result<i64, ApiError> {
+        match q.get(k) {
+            Some(s) => s
+                .parse::<i64>()
+                .map_err(|_| ApiError::Validation(format!("{} must be a number", k))),
+            None => Ok(default),
+        }
+    };
+
+    let filter = JwstFilter {
+        program: text("program"),
+        suffix: text("suffix"),
+        instrument: text("instrument").map(|s| s.to_uppercase()),
+    };
+    let page = number("page", 1)?;
+    let per_page = match q.get("per_page") {
+        Some(_) => number("per_page", 24)?,
+        None => number("perPage", 24)?,
+    };
+
+    let feed = state.jwst_service.feed(&filter, page, per_page).await?;
+    Ok(Json(feed))
+}
@@ -0,0 +1,18 @@
+use axum::{extract::State, Json};
+use serde_json::Value;
+
+use crate::AppState;
+
+pub async fn metrics(State(state): State<AppState>) -> Json<Value> {
+    Json(serde_json::json!({
+        "rate_limit": state.http.rate_limit_stats(),
+        "circuits": state.http.circuit_status(),
+    }))
+}
+
+// Квота апстримов по последним заголовкам X-RateLimit-* и блокировки по Retry-After
+pub async fn quota(State(state): State<AppState>) -> Json<Value> {
+    Json(serde_json::json!({
+        "hosts": state.http.quota_status(),
+    }))
+}
@@ -1,12 +1,26 @@
+pub mod apod;
+pub mod astro;
 pub mod health;
 pub mod iss;
+pub mod jobs;
+pub mod jwst;
+pub mod metrics;
+pub mod neo;
 pub mod osdr;
 pub mod space;
+pub mod space_weather;
 
+pub use apod::{apod_by_date, apod_list};
+pub use astro::{astro_events, astro_sky};
 pub use health::health;
 pub use iss::{last_iss, trigger_iss, iss_trend};
+pub use jobs::{job_get, job_list};
+pub use jwst::jwst_feed;
+pub use metrics::{metrics, quota};
+pub use neo::{neo_approaches, neo_object, neo_rankings};
 pub use osdr::{osdr_list, osdr_sync};
-pub use space::{space_latest, space_refresh, space_summary};
+pub use space::{space_at, space_history, space_latest, space_refresh, space_summary, space_typed};
+pub use space_weather::{cme_arrivals, flare_stats, space_weather_event};
 
 
 
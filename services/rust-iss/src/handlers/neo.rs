@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use chrono::{Days, NaiveDate, Utc};
+
+use crate::domain::{ApiError, NeoApproachFilter, NeoApproaches, NeoObjectRecord, NeoRanking};
+use crate::AppState;
+
+fn parse_opt<T: std::str::FromStr>(q: &HashMap<String, String>, key: &str) -> Result<Option<T>, ApiError> {
+    q.get(key)
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            s.parse::<T>()
+                .map_err(|_| ApiError::Validation(format!("invalid {}: {}", key, s)))
+        })
+        .transpose()
+}
+
+// /neo/approaches?from=&to=&hazardous=&max_distance_ld=&body=Earth&limit= — по умолчанию неделя вперёд
+pub async fn neo_approaches(
+    Query(q): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> Result<Json<NeoApproaches>, ApiError> {
+    let today = Utc::now().date_naive();
+    let from = parse_opt::<NaiveDate>(&q, "from")?.unwrap_or(today);
+    let filter = NeoApproachFilter {
+        from,
+        to: parse_opt::<NaiveDate>(&q, "to")?.unwrap_or(from + Days::new(7)),
+        hazardous: parse_opt::<bool>(&q, "hazardous")?,
+        max_distance_ld: parse_opt::<f64>(&q, "max_distance_ld")?,
+        orbiting_body: parse_opt::<String>(&q, "body")?.unwrap_or_else(|| "Earth".to_string()),
+    };
+    let limit = parse_opt::<i64>(&q, "limit")?.unwrap_or(200);
+
+    let approaches = state.neo_service.approaches(filter, limit).await?;
+    Ok(Json(approaches))
+}
+
+// /neo/rankings?days=&limit=10 — ближайшие и крупнейшие предстоящие сближения;
+// по умолчанию окно равно синхронизируемому NEO_AHEAD_DAYS
+pub async fn neo_rankings(
+    Query(q): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> Result<Json<NeoRanking>, ApiError> {
+    let days = parse_opt::<u64>(&q, "days")?;
+    let limit = parse_opt::<i64>(&q, "limit")?.unwrap_or(10);
+
+    let ranking = state.neo_service.ranking(days, limit).await?;
+    Ok(Json(ranking))
+}
+
+// /neo/{id} — карточка объекта NeoWs с орбитой и историей сближений
+pub async fn neo_object(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<NeoObjectRecord>, ApiError> {
+    let object = state.neo_service.object(id.trim()).await?;
+    Ok(Json(object))
+}
@@ -1,8 +1,9 @@
 use axum::extract::State;
+use axum::http::StatusCode;
 use axum::Json;
 use serde_json::Value;
 
-use crate::domain::ApiError;
+use crate::domain::{ApiError, JobKind, NewJob};
 use crate::AppState;
 
 pub async fn osdr_list(State(state): State<AppState>) -> Result<Json<Value>, ApiError> {
@@ -31,8 +32,22 @@ pub async fn osdr_list(State(state): State<AppState>) -> Result<Json<Value>, Api
     Ok(Json(serde_json::json!({ "items": out })))
 }
 
-pub async fn osdr_sync(State(state): State<AppState>) -> Result<Json<Value>, ApiError> {
-    let written = state.osdr_service.sync().await?;
-    Ok(Json(serde_json::json!({ "written": written })))
-}
+pub async fn osdr_sync(
+    State(state): State<AppState>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    // Синхронизация выполняется воркером очереди, клиент опрашивает /jobs/{id}
+    let job_id = state
+        .job_service
+        .enqueue(NewJob {
+            kind: JobKind::OsdrSync,
+            payload: serde_json::json!({}),
+            priority: 10,
+            idempotency_key: Some(JobKind::OsdrSync.as_str().to_string()),
+        })
+        .await?;
 
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({ "job_id": job_id, "poll": format!("/jobs/{}", job_id) })),
+    ))
+}
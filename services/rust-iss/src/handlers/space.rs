@@ -1,32 +1,121 @@
 use std::collections::HashMap;
 
-use axum::{extract::Path, extract::Query, extract::State, Json};
+use axum::{extract::Path, extract::Query, extract::State, http::StatusCode, Json};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde_json::Value;
 
-use crate::domain::{ApiError, SpaceSummary};
+use crate::domain::{
+    ApiError, JobKind, NewJob, SpaceCacheEntry, SpaceHistory, SpaceSummary, TypedSpaceEntry,
+};
+use crate::services::space::SPACE_SOURCES;
 use crate::AppState;
 
 pub async fn space_latest(
     Path(src): Path<String>,
+    Query(q): Query<HashMap<String, String>>,
     State(state): State<AppState>,
 ) -> Result<Json<Value>, ApiError> {
-    let result = state.space_service.get_latest(&src).await?;
+    let result = state.space_service.get_latest(&src, parse_max_age(&q)?).await?;
     Ok(Json(result))
 }
 
+pub async fn space_history(
+    Path(src): Path<String>,
+    Query(q): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> Result<Json<SpaceHistory>, ApiError> {
+    let from = q.get("from").map(|s| parse_ts(s)).transpose()?;
+    let to = q.get("to").map(|s| parse_ts(s)).transpose()?;
+    let limit = q
+        .get("limit")
+        .map(|s| {
+            s.trim()
+                .parse::<i64>()
+                .map_err(|_| ApiError::Validation(format!("invalid limit: {}", s)))
+        })
+        .transpose()?
+        .unwrap_or(50);
+
+    let history = state
+        .space_service
+        .history(&src, from, to, limit, q.get("cursor").map(|s| s.as_str()))
+        .await?;
+    Ok(Json(history))
+}
+
+pub async fn space_at(
+    Path(src): Path<String>,
+    Query(q): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> Result<Json<SpaceCacheEntry>, ApiError> {
+    let ts = q
+        .get("ts")
+        .ok_or_else(|| ApiError::Validation("ts is required".to_string()))?;
+    let entry = state.space_service.get_at(&src, parse_ts(ts)?).await?;
+    Ok(Json(entry))
+}
+
+pub async fn space_typed(
+    Path(src): Path<String>,
+    Query(q): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> Result<Json<TypedSpaceEntry>, ApiError> {
+    let entry = state.space_service.get_typed(&src, parse_max_age(&q)?).await?;
+    Ok(Json(entry))
+}
+
 pub async fn space_refresh(
     Query(q): Query<HashMap<String, String>>,
     State(state): State<AppState>,
-) -> Result<Json<Value>, ApiError> {
+) -> Result<(StatusCode, Json<Value>), ApiError> {
     let list = q
         .get("src")
         .cloned()
-        .unwrap_or_else(|| "apod,neo,flr,cme,spacex".to_string());
+        .unwrap_or_else(|| SPACE_SOURCES.join(","));
 
-    let sources: Vec<&str> = list.split(',').map(|x| x.trim()).collect();
-    let done = state.space_service.refresh(&sources).await?;
+    let mut sources: Vec<String> = list
+        .split(',')
+        .map(|x| x.trim().to_string())
+        .filter(|x| !x.is_empty())
+        .collect();
+    if let Some(bad) = sources.iter().find(|s| !SPACE_SOURCES.contains(&s.as_str())) {
+        return Err(ApiError::Validation(format!("unknown source: {}", bad)));
+    }
+    sources.sort();
+    sources.dedup();
 
-    Ok(Json(serde_json::json!({ "refreshed": done })))
+    // ?wait=true — синхронное обновление с отчётом по каждому источнику
+    if matches!(q.get("wait").map(|s| s.as_str()), Some("1" | "true")) {
+        let refs: Vec<&str> = sources.iter().map(|s| s.as_str()).collect();
+        let report = state.space_service.refresh_manual(&refs).await?;
+        let status = if report.failed == 0 {
+            StatusCode::OK
+        } else if report.ok > 0 {
+            StatusCode::MULTI_STATUS
+        } else {
+            StatusCode::BAD_GATEWAY
+        };
+        return Ok((status, Json(serde_json::to_value(report).unwrap_or_default())));
+    }
+
+    let job_id = state
+        .job_service
+        .enqueue(NewJob {
+            kind: JobKind::SpaceRefresh,
+            payload: serde_json::json!({ "sources": sources }),
+            priority: 10,
+            idempotency_key: Some(format!("{}:{}", JobKind::SpaceRefresh.as_str(), sources.join(","))),
+        })
+        .await?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({
+            "job_id": job_id,
+            "sources": sources,
+            "poll": format!("/jobs/{}", job_id),
+        })),
+    ))
 }
 
 pub async fn space_summary(State(state): State<AppState>) -> Result<Json<SpaceSummary>, ApiError> {
@@ -34,3 +123,23 @@ pub async fn space_summary(State(state): State<AppState>) -> Result<Json<SpaceSu
     Ok(Json(summary))
 }
 
+// Принимает RFC 3339 или дату YYYY-MM-DD (начало суток UTC)
+fn parse_ts(s: &str) -> Result<DateTime<Utc>, ApiError> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|ndt| ndt.and_utc())
+        .ok_or_else(|| ApiError::Validation(format!("invalid timestamp: {}", s)))
+}
+
+fn parse_max_age(q: &HashMap<String, String>) -> Result<Option<u64>, ApiError> {
+    q.get("max_age")
+        .map(|s| {
+            s.parse::<u64>()
+                .map_err(|_| ApiError::Validation(format!("invalid max_age: {}", s)))
+        })
+        .transpose()
+}
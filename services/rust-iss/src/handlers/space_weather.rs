@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use chrono::NaiveDate;
+
+use crate::domain::{ApiError, CmeArrivals, FlareStats, SpaceWeatherChain, StatsBucket};
+use crate::AppState;
+
+// /space-weather/events/{id} — причины и последствия события DONKI
+pub async fn space_weather_event(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<SpaceWeatherChain>, ApiError> {
+    let chain = state.space_weather_service.chain(id.trim()).await?;
+    Ok(Json(chain))
+}
+
+// /space-weather/flares/stats?from=YYYY-MM-DD&to=YYYY-MM-DD&bucket=day|month
+pub async fn flare_stats(
+    Query(q): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> Result<Json<FlareStats>, ApiError> {
+    let text = |k: &str| q.get(k).map(|s| s.trim()).filter(|s| !s.is_empty());
+    let date = |k: &str| -> Result<Option<NaiveDate>, ApiError> {
+        text(k)
+            .map(|s| {
+                NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                    .map_err(|_| ApiError::Validation(format!("invalid {}: {}", k, s)))
+            })
+            .transpose()
+    };
+
+    let bucket = text("bucket")
+        .map(|s| StatsBucket::parse(s).ok_or_else(|| ApiError::Validation(format!("invalid bucket: {}", s))))
+        .transpose()?;
+
+    let stats = state.flare_service.stats(date("from")?, date("to")?, bucket).await?;
+    Ok(Json(stats))
+}
+
+// /space-weather/cme/arrivals?past_hours=12 — предстоящие удары CME по Земле
+pub async fn cme_arrivals(
+    Query(q): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> Result<Json<CmeArrivals>, ApiError> {
+    let past_hours = match q.get("past_hours").map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        Some(s) => s
+            .parse::<i64>()
+            .map_err(|_| ApiError::Validation(format!("invalid past_hours: {}", s)))?,
+        None => 12,
+    };
+
+    let arrivals = state.space_weather_service.cme_arrivals(past_hours).await?;
+    Ok(Json(arrivals))
+}
@@ -1,8 +1,13 @@
+use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
 
-use sqlx::{postgres::PgPoolOptions, PgPool};
-use tracing::{error, info};
+use serde_json::Value;
+use sqlx::pool::PoolConnection;
+use sqlx::{postgres::PgPoolOptions, PgConnection, PgPool, Postgres};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
 pub mod config;
@@ -15,10 +20,13 @@ pub mod routes;
 pub mod app_state;
 
 use config::Config;
-use domain::ApiError;
-use repo::{CacheRepo, IssRepo, OsdrRepo};
-use clients::{HttpClient, IssClient, NasaClient, SpaceXClient};
-use services::{IssService, OsdrService, SpaceService};
+use domain::{ApiError, Job, JobKind, NewJob};
+use repo::{ApodRepo, BackfillRepo, CacheRepo, IssRepo, JobRepo, JwstRepo, NeoRepo, OsdrRepo, ReadCache, SpaceWeatherRepo, ThrottleRepo};
+use clients::{AstronomyClient, HttpClient, IssClient, JwstClient, NasaClient, SpaceXClient};
+use services::space::DONKI_SOURCES;
+use services::{
+    ApodService, AstroService, FlareService, IssService, JobService, JwstService, NeoService, OsdrService, SpaceService, SpaceWeatherService,
+};
 use app_state::AppState;
 
 #[tokio::main]
@@ -32,7 +40,7 @@ async fn main() -> anyhow::Result<()> {
 
     // Инициализация БД
     let pool = PgPoolOptions::new()
-        .max_connections(5)
+        .max_connections(config.db_max_connections)
         .connect(&config.database_url)
         .await?;
     init_db(&pool).await?;
@@ -50,52 +58,130 @@ async fn main() -> anyhow::Result<()> {
         }
     };
 
+    let cache = ReadCache::new(redis, "rust_iss:");
+
     // Инициализация HTTP клиента
     let http_client = HttpClient::new(&config)?;
 
     // Инициализация клиентов API
     let iss_client = IssClient::new(http_client.clone());
     let nasa_client = NasaClient::new(http_client.clone());
-    let spacex_client = SpaceXClient::new(http_client);
+    let spacex_client = SpaceXClient::new(http_client.clone());
+    let jwst_client = JwstClient::new(http_client.clone(), &config.jwst);
+    let astronomy_client = AstronomyClient::new(http_client.clone(), &config.astronomy);
 
     // Инициализация репозиториев
     let iss_repo = IssRepo::new(pool.clone());
     let osdr_repo = OsdrRepo::new(pool.clone());
     let cache_repo = CacheRepo::new(pool.clone());
+    let job_repo = JobRepo::new(pool.clone());
+    let jwst_repo = JwstRepo::new(pool.clone());
+    let space_weather_repo = SpaceWeatherRepo::new(pool.clone());
 
     // Инициализация сервисов
-    let iss_service = Arc::new(IssService::new(iss_repo, iss_client));
+    let iss_service = Arc::new(IssService::new(
+        iss_repo,
+        iss_client,
+        cache.clone(),
+        config.read_cache.iss_last_ttl,
+        ThrottleRepo::new(pool.clone()),
+        config.manual_refresh_min_interval,
+    ));
     let osdr_service = Arc::new(OsdrService::new(
         osdr_repo,
         nasa_client.clone(),
         config.nasa_url.clone(),
         config.nasa_key.clone(),
+        cache.clone(),
+        config.read_cache.osdr_list_ttl,
+    ));
+    let space_weather_service = Arc::new(SpaceWeatherService::new(CacheRepo::new(pool.clone()), space_weather_repo));
+    let flare_service = Arc::new(FlareService::new(
+        SpaceWeatherRepo::new(pool.clone()),
+        BackfillRepo::new(pool.clone()),
+        nasa_client.clone(),
+        config.nasa_key.clone(),
+        &config.backfill,
+    ));
+    let neo_service = Arc::new(NeoService::new(
+        NeoRepo::new(pool.clone()),
+        CacheRepo::new(pool.clone()),
+        nasa_client.clone(),
+        config.nasa_key.clone(),
+        &config.neo,
+    ));
+    let apod_service = Arc::new(ApodService::new(
+        ApodRepo::new(pool.clone()),
+        BackfillRepo::new(pool.clone()),
+        CacheRepo::new(pool.clone()),
+        nasa_client.clone(),
+        config.nasa_key.clone(),
+        &config.backfill,
     ));
     let space_service = Arc::new(SpaceService::new(
         cache_repo,
         nasa_client,
         spacex_client,
-        config.nasa_key.clone(),
+        cache.clone(),
+        ThrottleRepo::new(pool.clone()),
+        &config,
     ));
+    let job_service = Arc::new(JobService::new(job_repo, config.jobs.clone()));
+    let jwst_service = Arc::new(JwstService::new(jwst_repo, jwst_client, &config.jwst));
+    let astro_service = Arc::new(AstroService::new(astronomy_client, cache.clone(), &config.astronomy));
 
     let state = AppState {
         config: config.clone(),
         pool,
-        redis,
+        cache,
+        http: http_client,
         iss_service,
         osdr_service,
         space_service,
+        space_weather_service,
+        flare_service,
+        job_service,
+        jwst_service,
+        neo_service,
+        astro_service,
+        apod_service,
     };
 
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
     // Запуск фоновых задач с advisory locks
-    start_background_tasks(state.clone());
+    let mut tasks = start_background_tasks(state.clone(), shutdown_rx.clone());
+    let (worker_tasks, worker_ids) = start_job_workers(state.clone(), shutdown_rx.clone());
+    tasks.extend(worker_tasks);
 
     // Создание роутера
-    let app = routes::create_router().with_state(state);
+    let app = routes::create_router().with_state(state.clone());
 
     let listener = tokio::net::TcpListener::bind(("0.0.0.0", 3000)).await?;
     info!("rust_iss listening on 0.0.0.0:3000");
-    axum::serve(listener, app.into_make_service()).await?;
+
+    // После сигнала сервер перестаёт принимать соединения и дожидается текущих запросов
+    let mut http_shutdown = shutdown_rx.clone();
+    let mut server = tokio::spawn(async move {
+        axum::serve(listener, app.into_make_service())
+            .with_graceful_shutdown(async move {
+                let _ = http_shutdown.changed().await;
+            })
+            .await
+    });
+
+    tokio::select! {
+        res = &mut server => {
+            res??;
+            return Err(anyhow::anyhow!("http server stopped unexpectedly"));
+        }
+        signal = shutdown_signal() => {
+            info!("{} received, shutting down", signal);
+        }
+    }
+
+    let _ = shutdown_tx.send(true);
+    drain(state, server, tasks, worker_ids).await;
     Ok(())
 }
 
@@ -149,136 +235,529 @@ async fn init_db(pool: &PgPool) -> anyhow::Result<()> {
     .execute(pool)
     .await?;
 
+    sqlx::query(
+        "ALTER TABLE space_cache
+            ADD COLUMN IF NOT EXISTS content_hash TEXT,
+            ADD COLUMN IF NOT EXISTS last_seen_at TIMESTAMPTZ NOT NULL DEFAULT now()"
+    )
+    .execute(pool)
+    .await?;
+
+    // Хэши для строк, записанных до появления колонки
+    sqlx::query(
+        "UPDATE space_cache
+         SET content_hash = md5(payload::text), last_seen_at = fetched_at
+         WHERE content_hash IS NULL"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS jwst_images(
+            id TEXT PRIMARY KEY,
+            observation_id TEXT,
+            program TEXT,
+            suffix TEXT,
+            instruments TEXT[] NOT NULL DEFAULT '{}',
+            image_url TEXT NOT NULL,
+            thumbnail_url TEXT,
+            description TEXT,
+            caption TEXT NOT NULL,
+            raw JSONB NOT NULL,
+            first_seen_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+            fetched_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_jwst_images_feed
+         ON jwst_images(first_seen_at DESC, id)"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS space_weather_events(
+            activity_id TEXT PRIMARY KEY,
+            kind TEXT NOT NULL,
+            start_time TIMESTAMPTZ NOT NULL,
+            end_time TIMESTAMPTZ,
+            summary TEXT,
+            link TEXT,
+            details JSONB NOT NULL,
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_space_weather_events_kind
+         ON space_weather_events(kind, start_time DESC)"
+    )
+    .execute(pool)
+    .await?;
+
+    // Без внешних ключей: связь может опережать появление события в таблице
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS space_weather_links(
+            cause_id TEXT NOT NULL,
+            effect_id TEXT NOT NULL,
+            PRIMARY KEY (cause_id, effect_id)
+        )"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_space_weather_links_effect
+         ON space_weather_links(effect_id)"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS neo_approaches(
+            neo_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            approach_date DATE NOT NULL,
+            approach_time TIMESTAMPTZ NOT NULL,
+            orbiting_body TEXT NOT NULL,
+            miss_distance_km DOUBLE PRECISION NOT NULL,
+            miss_distance_ld DOUBLE PRECISION NOT NULL,
+            miss_distance_au DOUBLE PRECISION NOT NULL,
+            velocity_km_s DOUBLE PRECISION NOT NULL,
+            diameter_min_m DOUBLE PRECISION,
+            diameter_max_m DOUBLE PRECISION,
+            absolute_magnitude DOUBLE PRECISION,
+            hazardous BOOLEAN NOT NULL,
+            sentry BOOLEAN NOT NULL DEFAULT false,
+            nasa_jpl_url TEXT,
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+            PRIMARY KEY (neo_id, approach_date, orbiting_body)
+        )"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_neo_approaches_time
+         ON neo_approaches(orbiting_body, approach_time)"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS neo_objects(
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            hazardous BOOLEAN NOT NULL,
+            payload JSONB NOT NULL,
+            fetched_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS apod_archive(
+            date DATE PRIMARY KEY,
+            title TEXT NOT NULL,
+            explanation TEXT NOT NULL,
+            media_type TEXT NOT NULL,
+            url TEXT,
+            hdurl TEXT,
+            thumbnail_url TEXT,
+            copyright TEXT,
+            service_version TEXT,
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_apod_archive_media
+         ON apod_archive(media_type, date)"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS backfill_progress(
+            job TEXT NOT NULL,
+            period_start DATE NOT NULL,
+            records BIGINT NOT NULL DEFAULT 0,
+            done_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+            PRIMARY KEY (job, period_start)
+        )"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS refresh_throttle(
+            key TEXT PRIMARY KEY,
+            last_at TIMESTAMPTZ NOT NULL
+        )"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS jobs(
+            id BIGSERIAL PRIMARY KEY,
+            kind TEXT NOT NULL,
+            payload JSONB NOT NULL DEFAULT '{}'::jsonb,
+            status TEXT NOT NULL DEFAULT 'queued',
+            priority INT NOT NULL DEFAULT 0,
+            attempts INT NOT NULL DEFAULT 0,
+            max_attempts INT NOT NULL DEFAULT 5,
+            idempotency_key TEXT,
+            last_error TEXT,
+            result JSONB,
+            run_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+            locked_at TIMESTAMPTZ,
+            locked_by TEXT,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+            finished_at TIMESTAMPTZ
+        )"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_jobs_pick
+         ON jobs(priority DESC, run_at, id) WHERE status = 'queued'"
+    )
+    .execute(pool)
+    .await?;
+
+    // Ключ идемпотентности уникален только среди активных задач
+    sqlx::query(
+        "CREATE UNIQUE INDEX IF NOT EXISTS ux_jobs_idempotency
+         ON jobs(idempotency_key) WHERE status IN ('queued', 'running')"
+    )
+    .execute(pool)
+    .await?;
+
     Ok(())
 }
 
-fn start_background_tasks(state: AppState) {
-    let config = state.config.clone();
-    let intervals = config.fetch_intervals.clone();
+fn start_background_tasks(state: AppState, shutdown: watch::Receiver<bool>) -> Vec<JoinHandle<()>> {
+    let intervals = state.config.fetch_intervals.clone();
+    let mut tasks = Vec::new();
 
-    // OSDR с advisory lock
+    // OSDR
     {
         let st = state.clone();
-        tokio::spawn(async move {
-            loop {
-                if let Err(e) = run_with_lock(&st.pool, "osdr_fetch", || async {
-                    st.osdr_service.sync().await
-                })
-                .await
-                {
-                    error!("osdr err: {:?}", e);
-                }
-                tokio::time::sleep(Duration::from_secs(intervals.osdr)).await;
-            }
-        });
+        tasks.push(spawn_periodic(&state.pool, &shutdown, "osdr_fetch", intervals.osdr, move || {
+            let st = st.clone();
+            async move { st.osdr_service.sync().await.map(|_| ()) }
+        }));
     }
 
-    // ISS с advisory lock
+    // ISS
     {
         let st = state.clone();
-        let url = config.where_iss_url.clone();
-        tokio::spawn(async move {
-            loop {
-                if let Err(e) = run_with_lock(&st.pool, "iss_fetch", || async {
-                    st.iss_service.fetch_and_store(&url).await
-                })
-                .await
-                {
-                    error!("iss err: {:?}", e);
-                }
-                tokio::time::sleep(Duration::from_secs(intervals.iss)).await;
-            }
-        });
+        tasks.push(spawn_periodic(&state.pool, &shutdown, "iss_fetch", intervals.iss, move || {
+            let st = st.clone();
+            async move { st.iss_service.fetch_and_store(&st.config.where_iss_url).await }
+        }));
     }
 
     // APOD
     {
         let st = state.clone();
-        tokio::spawn(async move {
-            loop {
-                if let Err(e) = run_with_lock(&st.pool, "apod_fetch", || async {
-                    st.space_service.refresh(&["apod"]).await.map(|_| ())
-                })
-                .await
-                {
-                    error!("apod err: {:?}", e);
-                }
-                tokio::time::sleep(Duration::from_secs(intervals.apod)).await;
-            }
-        });
+        tasks.push(spawn_periodic(&state.pool, &shutdown, "apod_fetch", intervals.apod, move || {
+            let st = st.clone();
+            async move { st.space_service.refresh(&["apod"]).await?.ensure_all_ok().map(|_| ()) }
+        }));
     }
 
     // NeoWs
     {
         let st = state.clone();
-        tokio::spawn(async move {
-            loop {
-                if let Err(e) = run_with_lock(&st.pool, "neo_fetch", || async {
-                    st.space_service.refresh(&["neo"]).await.map(|_| ())
-                })
-                .await
-                {
-                    error!("neo err: {:?}", e);
-                }
-                tokio::time::sleep(Duration::from_secs(intervals.neo)).await;
-            }
-        });
+        tasks.push(spawn_periodic(&state.pool, &shutdown, "neo_fetch", intervals.neo, move || {
+            let st = st.clone();
+            async move { st.space_service.refresh(&["neo"]).await?.ensure_all_ok().map(|_| ()) }
+        }));
+    }
+
+    // Календарь сближений NEO: снимок ленты плюс окно вперёд
+    {
+        let st = state.clone();
+        tasks.push(spawn_periodic(&state.pool, &shutdown, "neo_approaches_sync", intervals.neo, move || {
+            let st = st.clone();
+            async move { st.neo_service.sync().await.map(|_| ()) }
+        }));
+    }
+
+    // Карточки NEO, открытые через /neo/{id}: обновление устаревших порциями
+    {
+        let st = state.clone();
+        tasks.push(spawn_periodic(&state.pool, &shutdown, "neo_objects_refresh", intervals.neo, move || {
+            let st = st.clone();
+            async move { st.neo_service.refresh_objects().await.map(|_| ()) }
+        }));
     }
 
     // DONKI
     {
         let st = state.clone();
-        tokio::spawn(async move {
-            loop {
-                if let Err(e) = run_with_lock(&st.pool, "donki_fetch", || async {
-                    st.space_service.refresh(&["flr", "cme"]).await.map(|_| ())
-                })
-                .await
-                {
-                    error!("donki err: {:?}", e);
-                }
-                tokio::time::sleep(Duration::from_secs(intervals.donki)).await;
+        tasks.push(spawn_periodic(&state.pool, &shutdown, "donki_fetch", intervals.donki, move || {
+            let st = st.clone();
+            async move {
+                let report = st.space_service.refresh(DONKI_SOURCES).await?;
+                // Граф событий строится и из неизменившихся снимков, поэтому до проверки ошибок
+                st.space_weather_service.ingest_latest().await?;
+                report.ensure_all_ok().map(|_| ())
             }
-        });
+        }));
     }
 
     // SpaceX
     {
         let st = state.clone();
-        tokio::spawn(async move {
-            loop {
-                if let Err(e) = run_with_lock(&st.pool, "spacex_fetch", || async {
-                    st.space_service.refresh(&["spacex"]).await.map(|_| ())
-                })
-                .await
-                {
-                    error!("spacex err: {:?}", e);
+        tasks.push(spawn_periodic(&state.pool, &shutdown, "spacex_fetch", intervals.spacex, move || {
+            let st = st.clone();
+            async move { st.space_service.refresh(&["spacex"]).await?.ensure_all_ok().map(|_| ()) }
+        }));
+    }
+
+    // История вспышек DONKI — несколько окон за запуск, пока каталог не догружен
+    {
+        let st = state.clone();
+        tasks.push(spawn_periodic(&state.pool, &shutdown, "flr_backfill", intervals.backfill, move || {
+            let st = st.clone();
+            async move { st.flare_service.backfill().await.map(|_| ()) }
+        }));
+    }
+
+    // Архив APOD за APOD_BACKFILL_YEARS лет — окнами, с паузой при низкой квоте NASA
+    {
+        let st = state.clone();
+        tasks.push(spawn_periodic(&state.pool, &shutdown, "apod_backfill", intervals.backfill, move || {
+            let st = st.clone();
+            async move { st.apod_service.backfill().await.map(|_| ()) }
+        }));
+    }
+
+    // JWST — только при заданном JWST_API_KEY
+    if state.jwst_service.is_configured() {
+        let st = state.clone();
+        tasks.push(spawn_periodic(&state.pool, &shutdown, "jwst_fetch", intervals.jwst, move || {
+            let st = st.clone();
+            async move { st.jwst_service.sync().await.map(|_| ()) }
+        }));
+    } else {
+        info!("JWST_API_KEY is not set, JWST sync disabled");
+    }
+
+    // Очистка space_cache по политике хранения — через очередь, результат виден в /jobs
+    {
+        let st = state.clone();
+        let every = state.config.retention.prune_every_seconds;
+        tasks.push(spawn_periodic(&state.pool, &shutdown, "cache_prune", every, move || {
+            let st = st.clone();
+            async move {
+                st.job_service
+                    .enqueue(NewJob {
+                        kind: JobKind::CachePrune,
+                        payload: serde_json::json!({}),
+                        priority: 0,
+                        idempotency_key: Some(JobKind::CachePrune.as_str().to_string()),
+                    })
+                    .await
+                    .map(|_| ())
+            }
+        }));
+    }
+
+    tasks
+}
+
+// Периодическая задача под advisory lock: новый запуск не начинается после сигнала остановки,
+// а ожидание между запусками прерывается сразу
+fn spawn_periodic<F, Fut>(
+    pool: &PgPool,
+    shutdown: &watch::Receiver<bool>,
+    lock_name: &'static str,
+    every_secs: u64,
+    f: F,
+) -> JoinHandle<()>
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), ApiError>> + Send,
+{
+    let pool = pool.clone();
+    let mut shutdown = shutdown.clone();
+    tokio::spawn(async move {
+        while !*shutdown.borrow() {
+            if let Err(e) = run_with_lock(&pool, lock_name, &f).await {
+                error!("{} err: {:?}", lock_name, e);
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(every_secs)) => {}
+                _ = shutdown.changed() => {}
+            }
+        }
+        info!("{} stopped", lock_name);
+    })
+}
+
+fn start_job_workers(state: AppState, shutdown: watch::Receiver<bool>) -> (Vec<JoinHandle<()>>, Vec<String>) {
+    let jobs = state.config.jobs.clone();
+    let mut tasks = Vec::new();
+    let mut worker_ids = Vec::new();
+
+    // Возврат в очередь задач, зависших у упавших воркеров
+    {
+        let st = state.clone();
+        let mut shutdown = shutdown.clone();
+        let every = Duration::from_secs((jobs.lease_seconds / 2).max(10));
+        tasks.push(tokio::spawn(async move {
+            while !*shutdown.borrow() {
+                match st.job_service.requeue_stale().await {
+                    Ok(0) => {}
+                    Ok(n) => warn!("requeued {} stale jobs", n),
+                    Err(e) => error!("job reaper err: {:?}", e),
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(every) => {}
+                    _ = shutdown.changed() => {}
+                }
+            }
+        }));
+    }
+
+    for n in 0..jobs.workers {
+        let st = state.clone();
+        let mut shutdown = shutdown.clone();
+        let worker_id = format!("{}-{}", std::process::id(), n);
+        worker_ids.push(worker_id.clone());
+        let poll = Duration::from_millis(jobs.poll_interval_ms);
+        tasks.push(tokio::spawn(async move {
+            // После сигнала остановки воркер доделывает текущую задачу и не берёт новых
+            while !*shutdown.borrow() {
+                match st.job_service.claim_next(&worker_id).await {
+                    Ok(Some(job)) => run_job(&st, &worker_id, job).await,
+                    Ok(None) => {
+                        tokio::select! {
+                            _ = tokio::time::sleep(poll) => {}
+                            _ = shutdown.changed() => {}
+                        }
+                    }
+                    Err(e) => {
+                        error!("job worker {} err: {:?}", worker_id, e);
+                        tokio::select! {
+                            _ = tokio::time::sleep(poll) => {}
+                            _ = shutdown.changed() => {}
+                        }
+                    }
                 }
-                tokio::time::sleep(Duration::from_secs(intervals.spacex)).await;
             }
-        });
+            info!("job worker {} stopped", worker_id);
+        }));
+    }
+
+    (tasks, worker_ids)
+}
+
+async fn run_job(st: &AppState, worker_id: &str, job: Job) {
+    info!("job {} ({}) started, attempt {}/{}", job.id, job.kind, job.attempts, job.max_attempts);
+
+    // Пока задача выполняется, аренда продлевается — иначе долгую задачу
+    // вернул бы в очередь reaper и она выполнилась бы дважды
+    let execution = execute_job(st, &job);
+    tokio::pin!(execution);
+    let mut heartbeat = tokio::time::interval(st.job_service.heartbeat_interval());
+    heartbeat.tick().await;
+    let outcome = loop {
+        tokio::select! {
+            res = &mut execution => break res,
+            _ = heartbeat.tick() => match st.job_service.renew(&job, worker_id).await {
+                Ok(true) => {}
+                Ok(false) => warn!("job {} lease lost while running", job.id),
+                Err(e) => error!("job {} lease renew err: {:?}", job.id, e),
+            },
+        }
+    };
+
+    match outcome {
+        Ok(result) => {
+            if let Err(e) = st.job_service.complete(&job, result).await {
+                error!("job {} complete err: {:?}", job.id, e);
+            }
+        }
+        Err(err) => match st.job_service.fail(&job, &err.to_string()).await {
+            Ok(status) => warn!("job {} ({}) failed: {}, now {}", job.id, job.kind, err, status.as_str()),
+            Err(e) => error!("job {} fail err: {:?}", job.id, e),
+        },
+    }
+}
+
+async fn execute_job(st: &AppState, job: &Job) -> Result<Value, ApiError> {
+    match JobKind::parse(&job.kind) {
+        Some(JobKind::OsdrSync) => {
+            let written = run_with_lock(&st.pool, "osdr_fetch", || async {
+                st.osdr_service.sync().await
+            })
+            .await?;
+            Ok(serde_json::json!({ "written": written }))
+        }
+        Some(JobKind::SpaceRefresh) => {
+            let sources: Vec<String> = job
+                .payload
+                .get("sources")
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default();
+            let refs: Vec<&str> = sources.iter().map(|s| s.as_str()).collect();
+            // Интервал ручных обновлений проверяется только на первой попытке,
+            // иначе повтор после ошибки упёрся бы в собственную отметку
+            let report = if job.attempts <= 1 {
+                st.space_service.refresh_manual(&refs).await?
+            } else {
+                st.space_service.refresh(&refs).await?
+            };
+            // Повтор задачи только если не обновился ни один источник,
+            // чтобы не тратить квоту на уже обновлённые
+            let report = report.ensure_any_ok()?;
+            Ok(serde_json::to_value(report).unwrap_or_default())
+        }
+        Some(JobKind::CachePrune) => {
+            let report = st.space_service.prune().await?;
+            Ok(serde_json::to_value(report).unwrap_or_default())
+        }
+        None => Err(ApiError::Validation(format!("unknown job kind: {}", job.kind))),
     }
 }
 
-// Advisory lock для предотвращения наложения задач
+// Advisory lock для предотвращения наложения задач.
+// Блокировка сессионная, поэтому захват и освобождение идут через одно соединение;
+// если задачу прервали посреди работы, соединение закрывается и Postgres снимает блокировку сам
 async fn run_with_lock<F, Fut, T>(pool: &PgPool, lock_name: &str, f: F) -> Result<T, ApiError>
 where
     F: FnOnce() -> Fut,
-    Fut: std::future::Future<Output = Result<T, ApiError>>,
+    Fut: Future<Output = Result<T, ApiError>>,
 {
-    // Используем pg_advisory_lock для блокировки
     let lock_id = hash_lock_name(lock_name);
-    
+    let mut guard = LockConnection(Some(pool.acquire().await?));
+
     // Пытаемся получить блокировку (неблокирующая попытка)
     let acquired: bool = sqlx::query_scalar(
         "SELECT pg_try_advisory_lock($1)"
     )
     .bind(lock_id as i64)
-    .fetch_one(pool)
+    .fetch_one(guard.conn())
     .await?;
 
     if !acquired {
-        // Блокировка уже занята другим процессом
+        // Блокировка уже занята другим процессом; соединение чистое — возвращаем в пул
+        guard.release();
         return Err(ApiError::Internal(format!("Lock {} is already held", lock_name)));
     }
 
@@ -289,12 +768,35 @@ where
         "SELECT pg_advisory_unlock($1)"
     )
     .bind(lock_id as i64)
-    .fetch_one(pool)
+    .fetch_one(guard.conn())
     .await?;
+    guard.release();
 
     result
 }
 
+struct LockConnection(Option<PoolConnection<Postgres>>);
+
+impl LockConnection {
+    fn conn(&mut self) -> &mut PgConnection {
+        self.0.as_mut().expect("lock connection already released")
+    }
+
+    // Возврат соединения в пул — только когда блокировка на нём точно не удерживается
+    fn release(&mut self) {
+        self.0.take();
+    }
+}
+
+impl Drop for LockConnection {
+    fn drop(&mut self) {
+        // Соединение с удерживаемой блокировкой не возвращаем в пул
+        if let Some(conn) = self.0.take() {
+            drop(conn.detach());
+        }
+    }
+}
+
 fn hash_lock_name(name: &str) -> u32 {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
@@ -302,3 +804,86 @@ fn hash_lock_name(name: &str) -> u32 {
     name.hash(&mut hasher);
     hasher.finish() as u32
 }
+
+async fn shutdown_signal() -> &'static str {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("SIGINT handler err: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut s) => {
+                s.recv().await;
+            }
+            Err(e) => {
+                error!("SIGTERM handler err: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => "SIGINT",
+        _ = terminate => "SIGTERM",
+    }
+}
+
+// Ожидание HTTP-запросов и фоновых задач в пределах общего дедлайна
+async fn drain(
+    state: AppState,
+    mut server: JoinHandle<std::io::Result<()>>,
+    tasks: Vec<JoinHandle<()>>,
+    worker_ids: Vec<String>,
+) {
+    let grace = Duration::from_secs(state.config.shutdown_grace_seconds);
+    let deadline = tokio::time::Instant::now() + grace;
+
+    let http_drained = match tokio::time::timeout_at(deadline, &mut server).await {
+        Ok(Ok(Ok(()))) => true,
+        Ok(Ok(Err(e))) => {
+            error!("http server err: {}", e);
+            false
+        }
+        Ok(Err(e)) => {
+            error!("http server task err: {}", e);
+            false
+        }
+        Err(_) => {
+            server.abort();
+            false
+        }
+    };
+
+    let total = tasks.len();
+    let mut finished = 0;
+    for mut task in tasks {
+        match tokio::time::timeout_at(deadline, &mut task).await {
+            Ok(_) => finished += 1,
+            Err(_) => task.abort(),
+        }
+    }
+
+    // Задачи очереди, прерванные по дедлайну, возвращаются в очередь без расхода попытки
+    let released = match state.job_service.release(&worker_ids).await {
+        Ok(n) => n,
+        Err(e) => {
+            error!("job release err: {:?}", e);
+            0
+        }
+    };
+
+    // Закрытие пула закрывает соединения, а вместе с ними и оставшиеся advisory locks
+    state.pool.close().await;
+
+    info!(
+        "shutdown complete: http_drained={}, tasks_finished={}/{}, jobs_released={}",
+        http_drained, finished, total, released
+    );
+}
@@ -0,0 +1,129 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+use crate::domain::{ApiError, Apod, ApodFilter};
+
+#[async_trait]
+pub trait ApodRepository: Send + Sync {
+    async fn upsert(&self, items: &[Apod]) -> Result<u64, ApiError>;
+    async fn get(&self, date: NaiveDate) -> Result<Option<Apod>, ApiError>;
+    async fn list(&self, filter: &ApodFilter, limit: i64) -> Result<Vec<Apod>, ApiError>;
+    async fn random(&self, filter: &ApodFilter, limit: i64) -> Result<Vec<Apod>, ApiError>;
+}
+
+pub struct ApodRepo {
+    pool: PgPool,
+}
+
+impl ApodRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const APOD_COLUMNS: &str = "date, title, explanation, media_type, url, hdurl, thumbnail_url, copyright, service_version";
+
+fn map_apod(r: PgRow) -> Apod {
+    Apod {
+        date: r.get("date"),
+        title: r.get("title"),
+        explanation: r.get("explanation"),
+        media_type: r.get("media_type"),
+        url: r.get("url"),
+        hdurl: r.get("hdurl"),
+        thumbnail_url: r.get("thumbnail_url"),
+        copyright: r.get("copyright"),
+        service_version: r.get("service_version"),
+    }
+}
+
+#[async_trait]
+impl ApodRepository for ApodRepo {
+    async fn upsert(&self, items: &[Apod]) -> Result<u64, ApiError> {
+        let mut tx = self.pool.begin().await?;
+        let mut written = 0u64;
+
+        for a in items {
+            let res = sqlx::query(&format!(
+                "INSERT INTO apod_archive({})
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                 ON CONFLICT (date) DO UPDATE
+                 SET title = EXCLUDED.title,
+                     explanation = EXCLUDED.explanation,
+                     media_type = EXCLUDED.media_type,
+                     url = EXCLUDED.url,
+                     hdurl = EXCLUDED.hdurl,
+                     thumbnail_url = EXCLUDED.thumbnail_url,
+                     copyright = EXCLUDED.copyright,
+                     service_version = EXCLUDED.service_version,
+                     updated_at = now()",
+                APOD_COLUMNS
+            ))
+            .bind(a.date)
+            .bind(&a.title)
+            .bind(&a.explanation)
+            .bind(&a.media_type)
+            .bind(&a.url)
+            .bind(&a.hdurl)
+            .bind(&a.thumbnail_url)
+            .bind(&a.copyright)
+            .bind(&a.service_version)
+            .execute(&mut *tx)
+            .await?;
+            written += res.rows_affected();
+        }
+
+        tx.commit().await?;
+        Ok(written)
+    }
+
+    async fn get(&self, date: NaiveDate) -> Result<Option<Apod>, ApiError> {
+        let row = sqlx::query(&format!("SELECT {} FROM apod_archive WHERE date = $1", APOD_COLUMNS))
+            .bind(date)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(map_apod))
+    }
+
+    // Галерея: новые выпуски первыми
+    async fn list(&self, filter: &ApodFilter, limit: i64) -> Result<Vec<Apod>, ApiError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM apod_archive
+             WHERE date BETWEEN $1 AND $2
+               AND ($3::text IS NULL OR media_type = $3)
+             ORDER BY date DESC
+             LIMIT $4",
+            APOD_COLUMNS
+        ))
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(&filter.media_type)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(map_apod).collect())
+    }
+
+    // Весь архив — около десяти тысяч строк, ORDER BY random() по нему дёшев
+    async fn random(&self, filter: &ApodFilter, limit: i64) -> Result<Vec<Apod>, ApiError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM apod_archive
+             WHERE date BETWEEN $1 AND $2
+               AND ($3::text IS NULL OR media_type = $3)
+             ORDER BY random()
+             LIMIT $4",
+            APOD_COLUMNS
+        ))
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(&filter.media_type)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(map_apod).collect())
+    }
+}
@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+
+use crate::domain::ApiError;
+
+// Отметки о загруженных окнах истории: повторный запуск продолжает с места остановки
+#[async_trait]
+pub trait BackfillRepository: Send + Sync {
+    async fn done(&self, job: &str, from: NaiveDate, to: NaiveDate) -> Result<Vec<NaiveDate>, ApiError>;
+    async fn mark_done(&self, job: &str, period_start: NaiveDate, records: i64) -> Result<(), ApiError>;
+}
+
+pub struct BackfillRepo {
+    pool: PgPool,
+}
+
+impl BackfillRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl BackfillRepository for BackfillRepo {
+    async fn done(&self, job: &str, from: NaiveDate, to: NaiveDate) -> Result<Vec<NaiveDate>, ApiError> {
+        let rows: Vec<NaiveDate> = sqlx::query_scalar(
+            "SELECT period_start FROM backfill_progress
+             WHERE job = $1 AND period_start BETWEEN $2 AND $3
+             ORDER BY period_start"
+        )
+        .bind(job)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    async fn mark_done(&self, job: &str, period_start: NaiveDate, records: i64) -> Result<(), ApiError> {
+        sqlx::query(
+            "INSERT INTO backfill_progress(job, period_start, records)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (job, period_start) DO UPDATE
+             SET records = EXCLUDED.records, done_at = now()"
+        )
+        .bind(job)
+        .bind(period_start)
+        .bind(records)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
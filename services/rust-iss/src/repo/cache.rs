@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::{PgPool, Row};
 
@@ -8,15 +9,30 @@ use crate::domain::ApiError;
 #[async_trait]
 pub trait CacheRepository: Send + Sync {
     async fn get_latest(&self, source: &str) -> Result<Option<CacheEntry>, ApiError>;
-    async fn insert(&self, source: &str, payload: Value) -> Result<(), ApiError>;
+    async fn get_at(&self, source: &str, at: DateTime<Utc>) -> Result<Option<CacheEntry>, ApiError>;
+    async fn history(&self, query: &HistoryQuery) -> Result<Vec<CacheEntry>, ApiError>;
+    async fn insert(&self, source: &str, payload: Value) -> Result<bool, ApiError>;
+    async fn touch_latest(&self, source: &str) -> Result<bool, ApiError>;
+    async fn prune(&self, source: &str, keep_days: u32) -> Result<u64, ApiError>;
     async fn count_osdr(&self) -> Result<i64, ApiError>;
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheEntry {
+    pub id: i64,
     pub fetched_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
     pub payload: Value,
 }
 
+pub struct HistoryQuery {
+    pub source: String,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub before_id: Option<i64>,
+    pub limit: i64,
+}
+
 pub struct CacheRepo {
     pool: PgPool,
 }
@@ -27,30 +43,115 @@ impl CacheRepo {
     }
 }
 
+fn map_entry(r: sqlx::postgres::PgRow) -> CacheEntry {
+    CacheEntry {
+        id: r.get("id"),
+        fetched_at: r.get::<DateTime<Utc>, _>("fetched_at"),
+        last_seen_at: r.get::<DateTime<Utc>, _>("last_seen_at"),
+        payload: r.get("payload"),
+    }
+}
+
 #[async_trait]
 impl CacheRepository for CacheRepo {
     async fn get_latest(&self, source: &str) -> Result<Option<CacheEntry>, ApiError> {
         let row = sqlx::query(
-            "SELECT fetched_at, payload FROM space_cache
+            "SELECT id, fetched_at, last_seen_at, payload FROM space_cache
              WHERE source = $1 ORDER BY id DESC LIMIT 1"
         )
         .bind(source)
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(row.map(|r| CacheEntry {
-            fetched_at: r.get::<DateTime<Utc>, _>("fetched_at"),
-            payload: r.get("payload"),
-        }))
+        Ok(row.map(map_entry))
     }
 
-    async fn insert(&self, source: &str, payload: Value) -> Result<(), ApiError> {
-        sqlx::query("INSERT INTO space_cache(source, payload) VALUES ($1, $2)")
-            .bind(source)
-            .bind(payload)
-            .execute(&self.pool)
-            .await?;
-        Ok(())
+    async fn get_at(&self, source: &str, at: DateTime<Utc>) -> Result<Option<CacheEntry>, ApiError> {
+        // Снимок, актуальный на момент at, — последний полученный не позже него
+        let row = sqlx::query(
+            "SELECT id, fetched_at, last_seen_at, payload FROM space_cache
+             WHERE source = $1 AND fetched_at <= $2
+             ORDER BY fetched_at DESC, id DESC LIMIT 1"
+        )
+        .bind(source)
+        .bind(at)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(map_entry))
+    }
+
+    async fn history(&self, query: &HistoryQuery) -> Result<Vec<CacheEntry>, ApiError> {
+        // Keyset-пагинация по id: курсор — id последней отданной записи
+        let rows = sqlx::query(
+            "SELECT id, fetched_at, last_seen_at, payload FROM space_cache
+             WHERE source = $1
+               AND ($2::timestamptz IS NULL OR fetched_at >= $2)
+               AND ($3::timestamptz IS NULL OR fetched_at <= $3)
+               AND ($4::bigint IS NULL OR id < $4)
+             ORDER BY id DESC
+             LIMIT $5"
+        )
+        .bind(&query.source)
+        .bind(query.from)
+        .bind(query.to)
+        .bind(query.before_id)
+        .bind(query.limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(map_entry).collect())
+    }
+
+    async fn insert(&self, source: &str, payload: Value) -> Result<bool, ApiError> {
+        // Хэш считается по каноничному тексту jsonb (ключи упорядочены), поэтому
+        // снимок, совпадающий с последним сохранённым, только продлевает last_seen_at
+        let res = sqlx::query(
+            "WITH last AS (
+                 SELECT id, content_hash FROM space_cache
+                 WHERE source = $1 ORDER BY id DESC LIMIT 1
+             ), seen AS (
+                 UPDATE space_cache s SET last_seen_at = now()
+                 FROM last
+                 WHERE s.id = last.id AND last.content_hash = md5($2::jsonb::text)
+                 RETURNING s.id
+             )
+             INSERT INTO space_cache(source, payload, content_hash)
+             SELECT $1, $2, md5($2::jsonb::text)
+             WHERE NOT EXISTS (SELECT 1 FROM seen)"
+        )
+        .bind(source)
+        .bind(payload)
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn touch_latest(&self, source: &str) -> Result<bool, ApiError> {
+        let res = sqlx::query(
+            "UPDATE space_cache SET last_seen_at = now()
+             WHERE id = (SELECT id FROM space_cache WHERE source = $1 ORDER BY id DESC LIMIT 1)"
+        )
+        .bind(source)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn prune(&self, source: &str, keep_days: u32) -> Result<u64, ApiError> {
+        // Последний снимок источника не удаляется, даже если он старше окна хранения
+        let res = sqlx::query(
+            "DELETE FROM space_cache
+             WHERE source = $1
+               AND last_seen_at < now() - make_interval(days => $2)
+               AND id <> (SELECT max(id) FROM space_cache WHERE source = $1)"
+        )
+        .bind(source)
+        .bind(keep_days as i32)
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected())
     }
 
     async fn count_osdr(&self) -> Result<i64, ApiError> {
@@ -60,6 +161,3 @@ impl CacheRepository for CacheRepo {
         Ok(row.get::<i64, _>("c"))
     }
 }
-
-
-
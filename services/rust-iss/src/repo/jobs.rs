@@ -0,0 +1,227 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+use crate::domain::{ApiError, Job, JobStatus, NewJob};
+
+#[async_trait]
+pub trait JobRepository: Send + Sync {
+    async fn enqueue(&self, job: &NewJob, max_attempts: i32) -> Result<i64, ApiError>;
+    async fn get(&self, id: i64) -> Result<Option<Job>, ApiError>;
+    async fn list(&self, status: Option<JobStatus>, limit: i64) -> Result<Vec<Job>, ApiError>;
+    async fn claim_next(&self, worker_id: &str) -> Result<Option<Job>, ApiError>;
+    async fn complete(&self, id: i64, result: Value) -> Result<(), ApiError>;
+    async fn fail(&self, id: i64, error: &str, retry_in_secs: i64) -> Result<JobStatus, ApiError>;
+    async fn renew(&self, id: i64, worker_id: &str) -> Result<bool, ApiError>;
+    async fn requeue_stale(&self, lease_secs: i64) -> Result<u64, ApiError>;
+    async fn release(&self, worker_ids: &[String]) -> Result<u64, ApiError>;
+}
+
+pub struct JobRepo {
+    pool: PgPool,
+}
+
+impl JobRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const JOB_COLUMNS: &str = "id, kind, payload, status, priority, attempts, max_attempts,
+     idempotency_key, last_error, result, run_at, created_at, finished_at";
+
+fn map_job(r: PgRow) -> Job {
+    let status: String = r.get("status");
+    Job {
+        id: r.get("id"),
+        kind: r.get("kind"),
+        payload: r.get("payload"),
+        status: JobStatus::parse(&status).unwrap_or(JobStatus::Dead),
+        priority: r.get("priority"),
+        attempts: r.get("attempts"),
+        max_attempts: r.get("max_attempts"),
+        idempotency_key: r.get("idempotency_key"),
+        last_error: r.get("last_error"),
+        result: r.get("result"),
+        run_at: r.get::<DateTime<Utc>, _>("run_at"),
+        created_at: r.get::<DateTime<Utc>, _>("created_at"),
+        finished_at: r.get("finished_at"),
+    }
+}
+
+#[async_trait]
+impl JobRepository for JobRepo {
+    async fn enqueue(&self, job: &NewJob, max_attempts: i32) -> Result<i64, ApiError> {
+        // Пока задача с тем же ключом идемпотентности ждёт или выполняется,
+        // повторная постановка возвращает её id вместо новой записи
+        for _ in 0..3 {
+            let inserted: Option<i64> = sqlx::query_scalar(
+                "INSERT INTO jobs(kind, payload, priority, max_attempts, idempotency_key)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (idempotency_key) WHERE status IN ('queued', 'running')
+                 DO NOTHING
+                 RETURNING id"
+            )
+            .bind(job.kind.as_str())
+            .bind(&job.payload)
+            .bind(job.priority)
+            .bind(max_attempts)
+            .bind(&job.idempotency_key)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            if let Some(id) = inserted {
+                return Ok(id);
+            }
+
+            let existing: Option<i64> = sqlx::query_scalar(
+                "SELECT id FROM jobs
+                 WHERE idempotency_key = $1 AND status IN ('queued', 'running')"
+            )
+            .bind(&job.idempotency_key)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            // Задача могла завершиться между INSERT и SELECT — пробуем ещё раз
+            if let Some(id) = existing {
+                return Ok(id);
+            }
+        }
+
+        Err(ApiError::Internal(format!(
+            "Could not enqueue job {}",
+            job.kind.as_str()
+        )))
+    }
+
+    async fn get(&self, id: i64) -> Result<Option<Job>, ApiError> {
+        let row = sqlx::query(&format!("SELECT {} FROM jobs WHERE id = $1", JOB_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(map_job))
+    }
+
+    async fn list(&self, status: Option<JobStatus>, limit: i64) -> Result<Vec<Job>, ApiError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM jobs
+             WHERE ($1::text IS NULL OR status = $1)
+             ORDER BY id DESC
+             LIMIT $2",
+            JOB_COLUMNS
+        ))
+        .bind(status.map(|s| s.as_str()))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(map_job).collect())
+    }
+
+    async fn claim_next(&self, worker_id: &str) -> Result<Option<Job>, ApiError> {
+        // SKIP LOCKED позволяет нескольким воркерам (и инстансам) разбирать очередь без блокировок
+        let row = sqlx::query(&format!(
+            "UPDATE jobs
+             SET status = 'running', attempts = attempts + 1,
+                 locked_at = now(), locked_by = $1, updated_at = now()
+             WHERE id = (
+                 SELECT id FROM jobs
+                 WHERE status = 'queued' AND run_at <= now()
+                 ORDER BY priority DESC, run_at, id
+                 FOR UPDATE SKIP LOCKED
+                 LIMIT 1
+             )
+             RETURNING {}",
+            JOB_COLUMNS
+        ))
+        .bind(worker_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(map_job))
+    }
+
+    async fn complete(&self, id: i64, result: Value) -> Result<(), ApiError> {
+        sqlx::query(
+            "UPDATE jobs
+             SET status = 'done', result = $2, last_error = NULL,
+                 locked_at = NULL, locked_by = NULL,
+                 finished_at = now(), updated_at = now()
+             WHERE id = $1"
+        )
+        .bind(id)
+        .bind(result)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn fail(&self, id: i64, error: &str, retry_in_secs: i64) -> Result<JobStatus, ApiError> {
+        // Исчерпав попытки, задача уходит в dead letter и больше не выбирается воркерами
+        let status: String = sqlx::query_scalar(
+            "UPDATE jobs
+             SET status = CASE WHEN attempts >= max_attempts THEN 'dead' ELSE 'queued' END,
+                 finished_at = CASE WHEN attempts >= max_attempts THEN now() ELSE NULL END,
+                 run_at = now() + make_interval(secs => $3),
+                 last_error = $2,
+                 locked_at = NULL, locked_by = NULL, updated_at = now()
+             WHERE id = $1
+             RETURNING status"
+        )
+        .bind(id)
+        .bind(error)
+        .bind(retry_in_secs as f64)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(JobStatus::parse(&status).unwrap_or(JobStatus::Dead))
+    }
+
+    // Продление аренды работающим воркером; false — задачу уже забрали
+    async fn renew(&self, id: i64, worker_id: &str) -> Result<bool, ApiError> {
+        let res = sqlx::query(
+            "UPDATE jobs SET locked_at = now(), updated_at = now()
+             WHERE id = $1 AND locked_by = $2 AND status = 'running'"
+        )
+        .bind(id)
+        .bind(worker_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn requeue_stale(&self, lease_secs: i64) -> Result<u64, ApiError> {
+        // Задачи упавшего воркера возвращаются в очередь по истечении аренды.
+        // Задача, которая раз за разом роняет воркер, тоже расходует попытки и уходит в dead
+        let res = sqlx::query(
+            "UPDATE jobs
+             SET status = CASE WHEN attempts >= max_attempts THEN 'dead' ELSE 'queued' END,
+                 finished_at = CASE WHEN attempts >= max_attempts THEN now() ELSE NULL END,
+                 last_error = COALESCE(last_error, 'lease expired'),
+                 locked_at = NULL, locked_by = NULL, updated_at = now()
+             WHERE status = 'running' AND locked_at < now() - make_interval(secs => $1)"
+        )
+        .bind(lease_secs as f64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected())
+    }
+
+    async fn release(&self, worker_ids: &[String]) -> Result<u64, ApiError> {
+        let res = sqlx::query(
+            "UPDATE jobs
+             SET status = 'queued', attempts = GREATEST(attempts - 1, 0),
+                 locked_at = NULL, locked_by = NULL, updated_at = now()
+             WHERE status = 'running' AND locked_by = ANY($1)"
+        )
+        .bind(worker_ids)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected())
+    }
+}
@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+use crate::domain::{ApiError, JwstFilter, JwstImage};
+
+#[async_trait]
+pub trait JwstRepository: Send + Sync {
+    async fn upsert(&self, image: &JwstImage, raw: &Value) -> Result<(), ApiError>;
+    async fn list(&self, filter: &JwstFilter, limit: i64, offset: i64) -> Result<Vec<JwstImage>, ApiError>;
+    async fn count(&self, filter: &JwstFilter) -> Result<i64, ApiError>;
+}
+
+pub struct JwstRepo {
+    pool: PgPool,
+}
+
+impl JwstRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const FILTER: &str = "($1::text IS NULL OR program = $1)
+     AND ($2::text IS NULL OR suffix = $2)
+     AND ($3::text IS NULL OR upper($3) = ANY(instruments))";
+
+fn map_image(r: PgRow) -> JwstImage {
+    JwstImage {
+        id: r.get("id"),
+        observation_id: r.get("observation_id"),
+        program: r.get("program"),
+        suffix: r.get("suffix"),
+        instruments: r.get("instruments"),
+        image_url: r.get("image_url"),
+        thumbnail_url: r.get("thumbnail_url"),
+        description: r.get("description"),
+        caption: r.get("caption"),
+        fetched_at: r.get("fetched_at"),
+    }
+}
+
+#[async_trait]
+impl JwstRepository for JwstRepo {
+    async fn upsert(&self, image: &JwstImage, raw: &Value) -> Result<(), ApiError> {
+        // first_seen_at не меняется при повторной синхронизации — по нему стабильный порядок ленты
+        sqlx::query(
+            "INSERT INTO jwst_images(id, observation_id, program, suffix, instruments,
+                                     image_url, thumbnail_url, description, caption, raw)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+             ON CONFLICT (id) DO UPDATE
+             SET observation_id = EXCLUDED.observation_id,
+                 program = EXCLUDED.program,
+                 suffix = EXCLUDED.suffix,
+                 instruments = EXCLUDED.instruments,
+                 image_url = EXCLUDED.image_url,
+                 thumbnail_url = EXCLUDED.thumbnail_url,
+                 description = EXCLUDED.description,
+                 caption = EXCLUDED.caption,
+                 raw = EXCLUDED.raw,
+                 fetched_at = now()"
+        )
+        .bind(&image.id)
+        .bind(&image.observation_id)
+        .bind(&image.program)
+        .bind(&image.suffix)
+        .bind(&image.instruments)
+        .bind(&image.image_url)
+        .bind(&image.thumbnail_url)
+        .bind(&image.description)
+        .bind(&image.caption)
+        .bind(raw)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn list(&self, filter: &JwstFilter, limit: i64, offset: i64) -> Result<Vec<JwstImage>, ApiError> {
+        let rows = sqlx::query(&format!(
+            "SELECT id, observation_id, program, suffix, instruments, image_url,
+                    thumbnail_url, description, caption, fetched_at
+             FROM jwst_images
+             WHERE {}
+             ORDER BY first_seen_at DESC, id
+             LIMIT $4 OFFSET $5",
+            FILTER
+        ))
+        .bind(&filter.program)
+        .bind(&filter.suffix)
+        .bind(&filter.instrument)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(map_image).collect())
+    }
+
+    async fn count(&self, filter: &JwstFilter) -> Result<i64, ApiError> {
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM jwst_images WHERE {}", FILTER))
+            .bind(&filter.program)
+            .bind(&filter.suffix)
+            .bind(&filter.instrument)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(total)
+    }
+}
@@ -1,10 +1,26 @@
+pub mod apod;
+pub mod backfill;
 pub mod iss;
 pub mod osdr;
 pub mod cache;
+pub mod jobs;
+pub mod jwst;
+pub mod neo;
+pub mod read_cache;
+pub mod space_weather;
+pub mod throttle;
 
+pub use apod::ApodRepo;
+pub use backfill::BackfillRepo;
 pub use iss::IssRepo;
 pub use osdr::OsdrRepo;
 pub use cache::CacheRepo;
+pub use jobs::JobRepo;
+pub use jwst::JwstRepo;
+pub use neo::NeoRepo;
+pub use read_cache::ReadCache;
+pub use space_weather::SpaceWeatherRepo;
+pub use throttle::ThrottleRepo;
 
 
 
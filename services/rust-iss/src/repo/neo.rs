@@ -0,0 +1,211 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_json::Value;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+use crate::domain::{ApiError, NeoApproach, NeoApproachFilter};
+
+#[async_trait]
+pub trait NeoRepository: Send + Sync {
+    async fn upsert_approaches(&self, approaches: &[NeoApproach]) -> Result<u64, ApiError>;
+    async fn approaches(&self, filter: &NeoApproachFilter, limit: i64) -> Result<Vec<NeoApproach>, ApiError>;
+    async fn closest(&self, from: DateTime<Utc>, to: NaiveDate, limit: i64) -> Result<Vec<NeoApproach>, ApiError>;
+    async fn largest(&self, from: DateTime<Utc>, to: NaiveDate, limit: i64) -> Result<Vec<NeoApproach>, ApiError>;
+    async fn get_object(&self, id: &str) -> Result<Option<(Value, DateTime<Utc>)>, ApiError>;
+    async fn upsert_object(&self, id: &str, name: &str, hazardous: bool, payload: &Value) -> Result<(), ApiError>;
+    async fn stale_objects(&self, older_than_secs: u64, limit: i64) -> Result<Vec<String>, ApiError>;
+    async fn delete_object(&self, id: &str) -> Result<(), ApiError>;
+}
+
+pub struct NeoRepo {
+    pool: PgPool,
+}
+
+impl NeoRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const APPROACH_COLUMNS: &str = "neo_id, name, approach_date, approach_time, orbiting_body,
+    miss_distance_km, miss_distance_ld, miss_distance_au, velocity_km_s,
+    diameter_min_m, diameter_max_m, absolute_magnitude, hazardous, sentry, nasa_jpl_url";
+
+fn map_approach(r: PgRow) -> NeoApproach {
+    NeoApproach {
+        neo_id: r.get("neo_id"),
+        name: r.get("name"),
+        approach_date: r.get("approach_date"),
+        approach_time: r.get("approach_time"),
+        orbiting_body: r.get("orbiting_body"),
+        miss_distance_km: r.get("miss_distance_km"),
+        miss_distance_ld: r.get("miss_distance_ld"),
+        miss_distance_au: r.get("miss_distance_au"),
+        velocity_km_s: r.get("velocity_km_s"),
+        diameter_min_m: r.get("diameter_min_m"),
+        diameter_max_m: r.get("diameter_max_m"),
+        absolute_magnitude: r.get("absolute_magnitude"),
+        hazardous: r.get("hazardous"),
+        sentry: r.get("sentry"),
+        nasa_jpl_url: r.get("nasa_jpl_url"),
+    }
+}
+
+#[async_trait]
+impl NeoRepository for NeoRepo {
+    async fn upsert_approaches(&self, approaches: &[NeoApproach]) -> Result<u64, ApiError> {
+        let mut tx = self.pool.begin().await?;
+        let mut written = 0u64;
+
+        for a in approaches {
+            let res = sqlx::query(&format!(
+                "INSERT INTO neo_approaches({})
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+                 ON CONFLICT (neo_id, approach_date, orbiting_body) DO UPDATE
+                 SET name = EXCLUDED.name,
+                     approach_time = EXCLUDED.approach_time,
+                     miss_distance_km = EXCLUDED.miss_distance_km,
+                     miss_distance_ld = EXCLUDED.miss_distance_ld,
+                     miss_distance_au = EXCLUDED.miss_distance_au,
+                     velocity_km_s = EXCLUDED.velocity_km_s,
+                     diameter_min_m = EXCLUDED.diameter_min_m,
+                     diameter_max_m = EXCLUDED.diameter_max_m,
+                     absolute_magnitude = EXCLUDED.absolute_magnitude,
+                     hazardous = EXCLUDED.hazardous,
+                     sentry = EXCLUDED.sentry,
+                     nasa_jpl_url = EXCLUDED.nasa_jpl_url,
+                     updated_at = now()",
+                APPROACH_COLUMNS
+            ))
+            .bind(&a.neo_id)
+            .bind(&a.name)
+            .bind(a.approach_date)
+            .bind(a.approach_time)
+            .bind(&a.orbiting_body)
+            .bind(a.miss_distance_km)
+            .bind(a.miss_distance_ld)
+            .bind(a.miss_distance_au)
+            .bind(a.velocity_km_s)
+            .bind(a.diameter_min_m)
+            .bind(a.diameter_max_m)
+            .bind(a.absolute_magnitude)
+            .bind(a.hazardous)
+            .bind(a.sentry)
+            .bind(&a.nasa_jpl_url)
+            .execute(&mut *tx)
+            .await?;
+            written += res.rows_affected();
+        }
+
+        tx.commit().await?;
+        Ok(written)
+    }
+
+    async fn approaches(&self, filter: &NeoApproachFilter, limit: i64) -> Result<Vec<NeoApproach>, ApiError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM neo_approaches
+             WHERE approach_date BETWEEN $1 AND $2
+               AND orbiting_body = $3
+               AND ($4::bool IS NULL OR hazardous = $4)
+               AND ($5::float8 IS NULL OR miss_distance_ld <= $5)
+             ORDER BY approach_time, neo_id
+             LIMIT $6",
+            APPROACH_COLUMNS
+        ))
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(&filter.orbiting_body)
+        .bind(filter.hazardous)
+        .bind(filter.max_distance_ld)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(map_approach).collect())
+    }
+
+    async fn closest(&self, from: DateTime<Utc>, to: NaiveDate, limit: i64) -> Result<Vec<NeoApproach>, ApiError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM neo_approaches
+             WHERE orbiting_body = 'Earth' AND approach_time >= $1 AND approach_date <= $2
+             ORDER BY miss_distance_km, approach_time
+             LIMIT $3",
+            APPROACH_COLUMNS
+        ))
+        .bind(from)
+        .bind(to)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(map_approach).collect())
+    }
+
+    async fn largest(&self, from: DateTime<Utc>, to: NaiveDate, limit: i64) -> Result<Vec<NeoApproach>, ApiError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM neo_approaches
+             WHERE orbiting_body = 'Earth' AND approach_time >= $1 AND approach_date <= $2
+               AND diameter_max_m IS NOT NULL
+             ORDER BY diameter_max_m DESC, approach_time
+             LIMIT $3",
+            APPROACH_COLUMNS
+        ))
+        .bind(from)
+        .bind(to)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(map_approach).collect())
+    }
+
+    async fn get_object(&self, id: &str) -> Result<Option<(Value, DateTime<Utc>)>, ApiError> {
+        let row = sqlx::query("SELECT payload, fetched_at FROM neo_objects WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|r| (r.get("payload"), r.get("fetched_at"))))
+    }
+
+    async fn upsert_object(&self, id: &str, name: &str, hazardous: bool, payload: &Value) -> Result<(), ApiError> {
+        sqlx::query(
+            "INSERT INTO neo_objects(id, name, hazardous, payload)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (id) DO UPDATE
+             SET name = EXCLUDED.name,
+                 hazardous = EXCLUDED.hazardous,
+                 payload = EXCLUDED.payload,
+                 fetched_at = now()"
+        )
+        .bind(id)
+        .bind(name)
+        .bind(hazardous)
+        .bind(payload)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn stale_objects(&self, older_than_secs: u64, limit: i64) -> Result<Vec<String>, ApiError> {
+        let ids: Vec<String> = sqlx::query_scalar(
+            "SELECT id FROM neo_objects
+             WHERE fetched_at < now() - make_interval(secs => $1)
+             ORDER BY fetched_at
+             LIMIT $2"
+        )
+        .bind(older_than_secs as f64)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(ids)
+    }
+
+    async fn delete_object(&self, id: &str) -> Result<(), ApiError> {
+        sqlx::query("DELETE FROM neo_objects WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
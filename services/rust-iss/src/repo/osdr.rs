@@ -61,7 +61,7 @@ impl OsdrRepository for OsdrRepo {
             .bind(dataset_id)
             .bind(&item.title)
             .bind(&item.status)
-            .bind(&item.updated_at)
+            .bind(item.updated_at)
             .bind(&item.raw)
             .execute(&self.pool)
             .await?;
@@ -74,7 +74,7 @@ impl OsdrRepository for OsdrRepo {
             .bind::<Option<String>>(None)
             .bind(&item.title)
             .bind(&item.status)
-            .bind(&item.updated_at)
+            .bind(item.updated_at)
             .bind(&item.raw)
             .execute(&self.pool)
             .await?;
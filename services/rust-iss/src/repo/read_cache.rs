@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use redis::aio::MultiplexedConnection;
+use redis::AsyncCommands;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::domain::ApiError;
+
+// После ошибки Redis не трогаем его это время и работаем из памяти
+const REDIS_BACKOFF: Duration = Duration::from_secs(30);
+const REDIS_OP_TIMEOUT: Duration = Duration::from_millis(500);
+
+// Read-through кэш горячих чтений: Redis, а при его недоступности — память процесса.
+// Промах по ключу загружается одним вызовом, конкурентные запросы ждут его результата.
+#[derive(Clone)]
+pub struct ReadCache {
+    redis: Option<Arc<redis::Client>>,
+    conn: Arc<tokio::sync::Mutex<Option<MultiplexedConnection>>>,
+    redis_down_until: Arc<Mutex<Option<Instant>>>,
+    memory: Arc<Mutex<HashMap<String, (Instant, String)>>>,
+    loading: Arc<Mutex<HashMap<String, Gate>>>,
+    prefix: String,
+}
+
+type Gate = Arc<tokio::sync::Mutex<()>>;
+
+// Запись о загрузке ключа убирается и тогда, когда future запроса бросили (клиент отключился):
+// иначе ключи с координатами копились бы в карте бесконечно
+struct LoadingEntry<'a> {
+    loading: &'a Mutex<HashMap<String, Gate>>,
+    key: &'a str,
+    gate: &'a Gate,
+}
+
+impl Drop for LoadingEntry<'_> {
+    fn drop(&mut self) {
+        let mut loading = self.loading.lock().unwrap_or_else(|e| e.into_inner());
+        // Убирает последний участник: в карте и у нас по ссылке, других ожидающих нет.
+        // Ключ мог уже получить новый gate — его не трогаем
+        if Arc::strong_count(self.gate) <= 2 && loading.get(self.key).is_some_and(|g| Arc::ptr_eq(g, self.gate)) {
+            loading.remove(self.key);
+        }
+    }
+}
+
+impl ReadCache {
+    pub fn new(redis: Option<Arc<redis::Client>>, prefix: &str) -> Self {
+        Self {
+            redis,
+            conn: Arc::new(tokio::sync::Mutex::new(None)),
+            redis_down_until: Arc::new(Mutex::new(None)),
+            memory: Arc::new(Mutex::new(HashMap::new())),
+            loading: Arc::new(Mutex::new(HashMap::new())),
+            prefix: prefix.to_string(),
+        }
+    }
+
+    pub fn backend(&self) -> &'static str {
+        if self.redis_available() {
+            "redis"
+        } else {
+            "memory"
+        }
+    }
+
+    pub async fn get_or_load<T, F, Fut>(&self, key: &str, ttl: Duration, load: F) -> Result<T, ApiError>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, ApiError>>,
+    {
+        let key = format!("{}{}", self.prefix, key);
+        if let Some(v) = self.get::<T>(&key).await {
+            return Ok(v);
+        }
+
+        // Защита от stampede: загрузку ключа выполняет один запрос, остальные ждут и читают кэш
+        let gate = {
+            let mut loading = self.loading.lock().unwrap_or_else(|e| e.into_inner());
+            loading.entry(key.clone()).or_default().clone()
+        };
+        let _entry = LoadingEntry {
+            loading: &self.loading,
+            key: &key,
+            gate: &gate,
+        };
+        let _guard = gate.lock().await;
+
+        if let Some(v) = self.get::<T>(&key).await {
+            return Ok(v);
+        }
+
+        let result = load().await;
+        if let Ok(value) = &result {
+            self.set(&key, value, ttl).await;
+        }
+        result
+    }
+
+    pub async fn invalidate(&self, key: &str) {
+        let key = format!("{}{}", self.prefix, key);
+        self.memory
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&key);
+
+        if self.redis_available() {
+            if let Err(e) = self.redis_del(&key).await {
+                tracing::warn!("{}", e);
+            }
+        }
+    }
+
+    pub async fn invalidate_prefix(&self, prefix: &str) {
+        let prefix = format!("{}{}", self.prefix, prefix);
+        self.memory
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|k, _| !k.starts_with(&prefix));
+
+        if self.redis_available() {
+            if let Err(e) = self.redis_del_prefix(&prefix).await {
+                tracing::warn!("{}", e);
+            }
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let raw = if self.redis_available() {
+            match self.redis_get(key).await {
+                Ok(v) => v,
+                Err(e) => {
+                    tracing::warn!("{}", e);
+                    self.memory_get(key)
+                }
+            }
+        } else {
+            self.memory_get(key)
+        };
+
+        raw.and_then(|s| serde_json::from_str(&s).ok())
+    }
+
+    async fn set<T: Serialize>(&self, key: &str, value: &T, ttl: Duration) {
+        let Ok(raw) = serde_json::to_string(value) else {
+            return;
+        };
+
+        if self.redis_available() {
+            match self.redis_set(key, &raw, ttl).await {
+                Ok(()) => return,
+                Err(e) => tracing::warn!("{}", e),
+            }
+        }
+
+        self.memory
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key.to_string(), (Instant::now() + ttl, raw));
+    }
+
+    fn memory_get(&self, key: &str) -> Option<String> {
+        let mut memory = self.memory.lock().unwrap_or_else(|e| e.into_inner());
+        match memory.get(key) {
+            Some((expires, raw)) if *expires > Instant::now() => Some(raw.clone()),
+            Some(_) => {
+                memory.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn redis_available(&self) -> bool {
+        if self.redis.is_none() {
+            return false;
+        }
+        let down_until = self.redis_down_until.lock().unwrap_or_else(|e| e.into_inner());
+        !matches!(*down_until, Some(t) if t > Instant::now())
+    }
+
+    fn mark_redis_down(&self) {
+        *self.redis_down_until.lock().unwrap_or_else(|e| e.into_inner()) =
+            Some(Instant::now() + REDIS_BACKOFF);
+    }
+
+    async fn connection(&self) -> Result<MultiplexedConnection, ApiError> {
+        let client = self
+            .redis
+            .as_ref()
+            .ok_or_else(|| ApiError::Redis("redis is not configured".to_string()))?;
+
+        let mut conn = self.conn.lock().await;
+        if let Some(c) = conn.as_ref() {
+            return Ok(c.clone());
+        }
+
+        let c = tokio::time::timeout(REDIS_OP_TIMEOUT, client.get_multiplexed_tokio_connection())
+            .await
+            .map_err(|_| ApiError::Redis("connect timeout".to_string()))?
+            .map_err(|e| ApiError::Redis(format!("connect: {}", e)))?;
+        *conn = Some(c.clone());
+        Ok(c)
+    }
+
+    // Любая ошибка Redis сбрасывает соединение и включает работу из памяти на REDIS_BACKOFF
+    async fn redis_op<T, F, Fut>(&self, op: F) -> Result<T, ApiError>
+    where
+        F: FnOnce(MultiplexedConnection) -> Fut,
+        Fut: Future<Output = redis::RedisResult<T>>,
+    {
+        if !self.redis_available() {
+            return Err(ApiError::Redis("redis is unavailable".to_string()));
+        }
+
+        let res = match self.connection().await {
+            Ok(conn) => match tokio::time::timeout(REDIS_OP_TIMEOUT, op(conn)).await {
+                Ok(Ok(v)) => Ok(v),
+                Ok(Err(e)) => Err(ApiError::Redis(e.to_string())),
+                Err(_) => Err(ApiError::Redis("operation timeout".to_string())),
+            },
+            Err(e) => Err(e),
+        };
+
+        if res.is_err() {
+            self.conn.lock().await.take();
+            self.mark_redis_down();
+        }
+        res
+    }
+
+    async fn redis_get(&self, key: &str) -> Result<Option<String>, ApiError> {
+        self.redis_op(|mut c| async move { c.get::<_, Option<String>>(key).await })
+            .await
+    }
+
+    async fn redis_set(&self, key: &str, raw: &str, ttl: Duration) -> Result<(), ApiError> {
+        let ttl_ms = ttl.as_millis().max(1) as u64;
+        self.redis_op(|mut c| async move { c.pset_ex::<_, _, ()>(key, raw, ttl_ms).await })
+            .await
+    }
+
+    async fn redis_del(&self, key: &str) -> Result<(), ApiError> {
+        self.redis_op(|mut c| async move { c.del::<_, ()>(key).await })
+            .await
+    }
+
+    async fn redis_del_prefix(&self, prefix: &str) -> Result<(), ApiError> {
+        let pattern = format!("{}*", prefix);
+        self.redis_op(|mut c| async move {
+            let keys: Vec<String> = {
+                let mut iter = c.scan_match::<_, String>(&pattern).await?;
+                let mut keys = Vec::new();
+                while let Some(k) = iter.next_item().await {
+                    keys.push(k);
+                }
+                keys
+            };
+            if !keys.is_empty() {
+                c.del::<_, ()>(keys).await?;
+            }
+            Ok(())
+        })
+        .await
+    }
+}
@@ -0,0 +1,177 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+use crate::domain::space_weather::orient;
+use crate::domain::{ApiError, SpaceWeatherEdge, SpaceWeatherEvent};
+
+#[async_trait]
+pub trait SpaceWeatherRepository: Send + Sync {
+    async fn upsert(&self, events: &[SpaceWeatherEvent]) -> Result<u64, ApiError>;
+    async fn find(&self, ids: &[String]) -> Result<Vec<SpaceWeatherEvent>, ApiError>;
+    async fn list(&self, kind: &str, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<SpaceWeatherEvent>, ApiError>;
+    async fn walk(&self, id: &str, max_depth: i32) -> Result<(Vec<(String, i32)>, Vec<(String, i32)>), ApiError>;
+    async fn edges(&self, ids: &[String]) -> Result<Vec<SpaceWeatherEdge>, ApiError>;
+}
+
+pub struct SpaceWeatherRepo {
+    pool: PgPool,
+}
+
+impl SpaceWeatherRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+fn map_event(r: PgRow) -> SpaceWeatherEvent {
+    SpaceWeatherEvent {
+        activity_id: r.get("activity_id"),
+        kind: r.get("kind"),
+        start_time: r.get("start_time"),
+        end_time: r.get("end_time"),
+        summary: r.get("summary"),
+        link: r.get("link"),
+        linked: Vec::new(),
+        details: r.get("details"),
+    }
+}
+
+#[async_trait]
+impl SpaceWeatherRepository for SpaceWeatherRepo {
+    // Событие и его связи пишутся в одной транзакции; связь может указывать
+    // на событие, которого ещё нет в таблице, — оно появится со следующей лентой
+    async fn upsert(&self, events: &[SpaceWeatherEvent]) -> Result<u64, ApiError> {
+        let mut tx = self.pool.begin().await?;
+        let mut written = 0u64;
+
+        for e in events {
+            let res = sqlx::query(
+                "INSERT INTO space_weather_events(activity_id, kind, start_time, end_time, summary, link, details)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)
+                 ON CONFLICT (activity_id) DO UPDATE
+                 SET kind = EXCLUDED.kind,
+                     start_time = EXCLUDED.start_time,
+                     end_time = EXCLUDED.end_time,
+                     summary = EXCLUDED.summary,
+                     link = EXCLUDED.link,
+                     details = EXCLUDED.details,
+                     updated_at = now()
+                 WHERE space_weather_events.details IS DISTINCT FROM EXCLUDED.details"
+            )
+            .bind(&e.activity_id)
+            .bind(&e.kind)
+            .bind(e.start_time)
+            .bind(e.end_time)
+            .bind(&e.summary)
+            .bind(&e.link)
+            .bind(&e.details)
+            .execute(&mut *tx)
+            .await?;
+            written += res.rows_affected();
+
+            for other in &e.linked {
+                let edge = orient(&e.activity_id, other);
+                sqlx::query(
+                    "INSERT INTO space_weather_links(cause_id, effect_id) VALUES ($1, $2)
+                     ON CONFLICT DO NOTHING"
+                )
+                .bind(&edge.cause)
+                .bind(&edge.effect)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(written)
+    }
+
+    async fn find(&self, ids: &[String]) -> Result<Vec<SpaceWeatherEvent>, ApiError> {
+        let rows = sqlx::query(
+            "SELECT activity_id, kind, start_time, end_time, summary, link, details
+             FROM space_weather_events
+             WHERE activity_id = ANY($1)
+             ORDER BY start_time, activity_id"
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(map_event).collect())
+    }
+
+    // События типа с началом в [from, to)
+    async fn list(&self, kind: &str, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<SpaceWeatherEvent>, ApiError> {
+        let rows = sqlx::query(
+            "SELECT activity_id, kind, start_time, end_time, summary, link, details
+             FROM space_weather_events
+             WHERE kind = $1 AND start_time >= $2 AND start_time < $3
+             ORDER BY start_time, activity_id"
+        )
+        .bind(kind)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(map_event).collect())
+    }
+
+    // Предки и потомки события с расстоянием до него; глубина ограничена,
+    // так что случайный цикл в ссылках не зацикливает запрос
+    async fn walk(&self, id: &str, max_depth: i32) -> Result<(Vec<(String, i32)>, Vec<(String, i32)>), ApiError> {
+        let causes = sqlx::query(
+            "WITH RECURSIVE up(id, depth) AS (
+                SELECT $1::text, 0
+                UNION
+                SELECT l.cause_id, up.depth + 1
+                FROM space_weather_links l JOIN up ON l.effect_id = up.id
+                WHERE up.depth < $2
+             )
+             SELECT id, MIN(depth) AS depth FROM up WHERE id <> $1 GROUP BY id ORDER BY depth, id"
+        )
+        .bind(id)
+        .bind(max_depth)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let effects = sqlx::query(
+            "WITH RECURSIVE down(id, depth) AS (
+                SELECT $1::text, 0
+                UNION
+                SELECT l.effect_id, down.depth + 1
+                FROM space_weather_links l JOIN down ON l.cause_id = down.id
+                WHERE down.depth < $2
+             )
+             SELECT id, MIN(depth) AS depth FROM down WHERE id <> $1 GROUP BY id ORDER BY depth, id"
+        )
+        .bind(id)
+        .bind(max_depth)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let pairs = |rows: Vec<PgRow>| rows.into_iter().map(|r| (r.get("id"), r.get("depth"))).collect();
+        Ok((pairs(causes), pairs(effects)))
+    }
+
+    async fn edges(&self, ids: &[String]) -> Result<Vec<SpaceWeatherEdge>, ApiError> {
+        let rows = sqlx::query(
+            "SELECT cause_id, effect_id FROM space_weather_links
+             WHERE cause_id = ANY($1) AND effect_id = ANY($1)
+             ORDER BY cause_id, effect_id"
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| SpaceWeatherEdge {
+                cause: r.get("cause_id"),
+                effect: r.get("effect_id"),
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::domain::ApiError;
+
+#[async_trait]
+pub trait ThrottleRepository: Send + Sync {
+    async fn try_acquire(&self, key: &str, min_interval_secs: u64) -> Result<bool, ApiError>;
+    async fn release(&self, key: &str) -> Result<(), ApiError>;
+}
+
+// Минимальный интервал между ручными обновлениями, общий для всех инстансов
+pub struct ThrottleRepo {
+    pool: PgPool,
+}
+
+impl ThrottleRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ThrottleRepository for ThrottleRepo {
+    async fn try_acquire(&self, key: &str, min_interval_secs: u64) -> Result<bool, ApiError> {
+        // Строка обновляется только если интервал истёк — атомарно для конкурирующих инстансов
+        let acquired: Option<i32> = sqlx::query_scalar(
+            "INSERT INTO refresh_throttle(key, last_at) VALUES ($1, now())
+             ON CONFLICT (key) DO UPDATE SET last_at = now()
+             WHERE refresh_throttle.last_at < now() - make_interval(secs => $2)
+             RETURNING 1"
+        )
+        .bind(key)
+        .bind(min_interval_secs as f64)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(acquired.is_some())
+    }
+
+    // Неудачное обновление не должно занимать интервал — повтор разрешается сразу
+    async fn release(&self, key: &str) -> Result<(), ApiError> {
+        sqlx::query("DELETE FROM refresh_throttle WHERE key = $1")
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
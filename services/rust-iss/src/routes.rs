@@ -7,13 +7,31 @@ use crate::AppState;
 pub fn create_router() -> Router<AppState> {
     Router::new()
         .route("/health", get(handlers::health))
+        .route("/metrics", get(handlers::metrics))
+        .route("/quota", get(handlers::quota))
         .route("/last", get(handlers::last_iss))
         .route("/fetch", get(handlers::trigger_iss))
         .route("/iss/trend", get(handlers::iss_trend))
         .route("/osdr/sync", get(handlers::osdr_sync))
         .route("/osdr/list", get(handlers::osdr_list))
         .route("/space/:src/latest", get(handlers::space_latest))
+        .route("/space/:src/typed", get(handlers::space_typed))
+        .route("/space/:src/history", get(handlers::space_history))
+        .route("/space/:src/at", get(handlers::space_at))
         .route("/space/refresh", get(handlers::space_refresh))
         .route("/space/summary", get(handlers::space_summary))
+        .route("/space-weather/events/:id", get(handlers::space_weather_event))
+        .route("/space-weather/flares/stats", get(handlers::flare_stats))
+        .route("/space-weather/cme/arrivals", get(handlers::cme_arrivals))
+        .route("/jobs", get(handlers::job_list))
+        .route("/jobs/:id", get(handlers::job_get))
+        .route("/jwst/feed", get(handlers::jwst_feed))
+        .route("/neo/approaches", get(handlers::neo_approaches))
+        .route("/neo/rankings", get(handlers::neo_rankings))
+        .route("/neo/:id", get(handlers::neo_object))
+        .route("/apod", get(handlers::apod_list))
+        .route("/apod/:date", get(handlers::apod_by_date))
+        .route("/astro/events", get(handlers::astro_events))
+        .route("/astro/sky", get(handlers::astro_sky))
 }
 
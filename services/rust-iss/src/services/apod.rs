@@ -0,0 +1,165 @@
+use chrono::{Days, Months, NaiveDate, Utc};
+
+use crate::clients::nasa::{NasaClient, NasaClientTrait};
+use crate::config::BackfillConfig;
+use crate::domain::{apod_first_date, parse_list, parse_one, ApiError, Apod, ApodFilter, ApodPage, SpacePayload};
+use crate::repo::apod::{ApodRepo, ApodRepository};
+use crate::repo::backfill::{BackfillRepo, BackfillRepository};
+use crate::repo::cache::{CacheRepo, CacheRepository};
+
+const BACKFILL_JOB: &str = "apod";
+const MAX_PAGE: i64 = 500;
+const MAX_RANDOM: i64 = 50;
+
+pub struct ApodService {
+    repo: ApodRepo,
+    progress: BackfillRepo,
+    cache_repo: CacheRepo,
+    client: NasaClient,
+    nasa_key: String,
+    config: BackfillConfig,
+}
+
+// APOD публикуется по времени восточного побережья США: «сегодня» по UTC
+// там может ещё не наступить, и апстрим отвечает 400. Архив гарантированно есть по вчера
+fn last_published() -> NaiveDate {
+    Utc::now().date_naive() - Days::new(1)
+}
+
+impl ApodService {
+    pub fn new(
+        repo: ApodRepo,
+        progress: BackfillRepo,
+        cache_repo: CacheRepo,
+        client: NasaClient,
+        nasa_key: String,
+        config: &BackfillConfig,
+    ) -> Self {
+        Self {
+            repo,
+            progress,
+            cache_repo,
+            client,
+            nasa_key,
+            config: config.clone(),
+        }
+    }
+
+    // Выпуск за дату: из архива, иначе разовый запрос к APOD с сохранением
+    pub async fn by_date(&self, date: NaiveDate) -> Result<Apod, ApiError> {
+        if let Some(apod) = self.repo.get(date).await? {
+            return Ok(apod);
+        }
+        // Сегодняшний выпуск по UTC может быть ещё не опубликован — апстрим ответил бы 400
+        if date < apod_first_date() || date > last_published() {
+            return Err(ApiError::NotFound(format!("no APOD for {}", date)));
+        }
+
+        let payload = self.client.fetch_apod_date(&self.nasa_key, date).await?;
+        let (apod, _) = parse_one::<Apod>(&payload)
+            .map_err(|e| ApiError::ExternalApi(format!("APOD validation failed: {}", e)))?;
+        self.repo.upsert(std::slice::from_ref(&apod)).await?;
+        Ok(apod)
+    }
+
+    // Просмотр архива за окно; random — столько случайных выпусков из окна вместо ленты
+    pub async fn browse(&self, filter: ApodFilter, random: Option<i64>, limit: i64) -> Result<ApodPage, ApiError> {
+        if filter.from > filter.to {
+            return Err(ApiError::Validation("from must not be after to".to_string()));
+        }
+        if filter
+            .media_type
+            .as_deref()
+            .is_some_and(|m| !matches!(m, "image" | "video" | "other"))
+        {
+            return Err(ApiError::Validation("media_type must be image, video or other".to_string()));
+        }
+
+        let items = match random {
+            Some(n) => self.repo.random(&filter, n.clamp(1, MAX_RANDOM)).await?,
+            None => self.repo.list(&filter, limit.clamp(1, MAX_PAGE)).await?,
+        };
+        Ok(ApodPage {
+            from: filter.from,
+            to: filter.to,
+            random: random.is_some(),
+            count: items.len(),
+            items,
+        })
+    }
+
+    // Догрузка архива за последние apod_years лет окнами по chunk_days, свежие окна первыми.
+    // Окно с последним днём остаётся открытым и перечитывается; при низком остатке квоты
+    // api.nasa.gov запуск прерывается — ежедневные ленты важнее истории
+    pub async fn backfill(&self) -> Result<u64, ApiError> {
+        let mut written = 0u64;
+
+        // Текущий выпуск уже лежит в снимке space_cache — он попадает в архив без запроса
+        if let Some(entry) = self.cache_repo.get_latest("apod").await? {
+            if let Ok((SpacePayload::Apod(apod), _)) = SpacePayload::parse("apod", &entry.payload) {
+                written += self.repo.upsert(&[apod]).await?;
+            }
+        }
+
+        // Сетка окон привязана к первому выпуску, а не к границе «N лет назад»:
+        // граница сдвигается каждый день, а отметки о готовых окнах должны совпадать.
+        // Окно, которое ещё не дошло до конца, одно; оно перечитывается каждый запуск
+        // сверх chunks_per_run, иначе занимало бы бюджет и архив не наполнялся бы
+        let last = last_published();
+        let from = last - Months::new(12 * self.config.apod_years);
+        let done = self.progress.done(BACKFILL_JOB, apod_first_date(), last).await?;
+
+        let mut open = None;
+        let mut closed = Vec::new();
+        let mut start = apod_first_date();
+        while start <= last {
+            let end = start + Days::new(self.config.chunk_days - 1);
+            if end > last {
+                open = Some((start, last));
+            } else if end >= from && done.binary_search(&start).is_err() {
+                closed.push((start, end));
+            }
+            start = end + Days::new(1);
+        }
+        closed.reverse();
+
+        if let Some((start, end)) = open {
+            if self.client.quota_low() {
+                tracing::info!("apod backfill: NASA quota is low, postponing");
+                return Ok(written);
+            }
+            match self.backfill_window(start, end).await {
+                Ok((upserted, _)) => written += upserted,
+                Err(e) => tracing::warn!("apod backfill {}..{} failed: {:?}", start, end, e),
+            }
+        }
+        for (start, end) in closed.into_iter().take(self.config.chunks_per_run as usize) {
+            if self.client.quota_low() {
+                tracing::info!("apod backfill: NASA quota is low, postponing");
+                break;
+            }
+            // Окно с ошибкой не отмечается и будет взято в следующий запуск, остальные идут дальше
+            match self.backfill_window(start, end).await {
+                Ok((upserted, records)) => {
+                    written += upserted;
+                    self.progress.mark_done(BACKFILL_JOB, start, records).await?;
+                }
+                Err(e) => tracing::warn!("apod backfill {}..{} failed: {:?}", start, end, e),
+            }
+        }
+
+        Ok(written)
+    }
+
+    async fn backfill_window(&self, start: NaiveDate, end: NaiveDate) -> Result<(u64, i64), ApiError> {
+        let payload = self.client.fetch_apod_range(&self.nasa_key, start, end).await?;
+        let (items, report) = parse_list::<Apod>(&payload)
+            .map_err(|e| ApiError::ExternalApi(format!("APOD {}..{}: {}", start, end, e)))?;
+        if report.rejected > 0 {
+            tracing::warn!("apod backfill {}..{}: {} entries rejected", start, end, report.rejected);
+        }
+        let written = self.repo.upsert(&items).await?;
+        tracing::info!("apod backfill {}..{}: {} entries", start, end, items.len());
+        Ok((written, items.len() as i64))
+    }
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use chrono::{Days, Duration as ChronoDuration, NaiveDate, Utc};
+
+use crate::clients::astronomy::{AstronomyClient, AstronomyClientTrait};
+use crate::config::AstronomyConfig;
+use crate::domain::ephemeris::{self, SkyReport};
+use crate::domain::{ApiError, AstroEvents};
+use crate::repo::ReadCache;
+
+pub struct AstroService {
+    client: AstronomyClient,
+    cache: ReadCache,
+    events_ttl: Duration,
+    bodies: Vec<String>,
+}
+
+// Округление до 0.1° (~11 км): соседние наблюдатели получают один и тот же кэш,
+// а время событий при таком сдвиге меняется на секунды
+fn round_coord(v: f64) -> f64 {
+    (v * 10.0).round() / 10.0
+}
+
+fn validate_coords(lat: f64, lon: f64) -> Result<(), ApiError> {
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+        return Err(ApiError::Validation("lat must be in [-90, 90], lon in [-180, 180]".to_string()));
+    }
+    Ok(())
+}
+
+impl AstroService {
+    pub fn new(client: AstronomyClient, cache: ReadCache, config: &AstronomyConfig) -> Self {
+        Self {
+            client,
+            cache,
+            events_ttl: config.events_ttl,
+            bodies: config.bodies.clone(),
+        }
+    }
+
+    pub async fn events(&self, lat: f64, lon: f64, days: u64) -> Result<AstroEvents, ApiError> {
+        validate_coords(lat, lon)?;
+        if !(1..=30).contains(&days) {
+            return Err(ApiError::Validation("days must be in [1, 30]".to_string()));
+        }
+
+        let (lat, lon) = (round_coord(lat), round_coord(lon));
+        let from = Utc::now().date_naive();
+        let to = from + Days::new(days);
+        let key = format!("astro:events:{:.1}:{:.1}:{}:{}", lat, lon, from, to);
+
+        self.cache
+            .get_or_load(&key, self.events_ttl, || async {
+                let mut events = Vec::new();
+                for body in &self.bodies {
+                    events.extend(self.client.fetch_events(body, lat, lon, from, to).await?);
+                }
+                events.sort_by_key(|e| e.peak.or(e.rise));
+
+                Ok(AstroEvents {
+                    latitude: lat,
+                    longitude: lon,
+                    from,
+                    to,
+                    events,
+                })
+            })
+            .await
+    }
+
+    // Локальный расчёт без внешнего API: положения берутся на текущий момент,
+    // если он попадает в сутки наблюдателя, иначе на их середину
+    pub fn sky(&self, lat: f64, lon: f64, date: Option<NaiveDate>) -> Result<SkyReport, ApiError> {
+        validate_coords(lat, lon)?;
+
+        let now = Utc::now();
+        let date = date.unwrap_or_else(|| now.date_naive());
+        let (start, end) = ephemeris::observer_day(date, lon);
+        let at = if now >= start && now < end {
+            now
+        } else {
+            start + ChronoDuration::hours(12)
+        };
+
+        Ok(ephemeris::sky_report(lat, lon, date, at))
+    }
+}
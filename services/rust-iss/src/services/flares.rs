@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveTime, Utc};
+
+use crate::clients::nasa::{NasaClient, NasaClientTrait};
+use crate::config::BackfillConfig;
+use crate::domain::{
+    ApiError, FlareBucket, FlareClass, FlareStats, FlareSummary, SpacePayload, SpaceWeatherEvent, StatsBucket,
+    FLARE_CLASSES,
+};
+use crate::repo::backfill::{BackfillRepo, BackfillRepository};
+use crate::repo::space_weather::{SpaceWeatherRepo, SpaceWeatherRepository};
+
+const BACKFILL_JOB: &str = "donki_flr";
+// DONKI дописывает недавние вспышки (пики, области) ещё несколько дней —
+// окно считается закрытым только после этого срока
+const SETTLE_DAYS: u64 = 7;
+const MAX_STATS_DAYS: i64 = 366 * 20;
+const DAILY_MAX_DAYS: i64 = 92;
+
+pub struct FlareService {
+    repo: SpaceWeatherRepo,
+    progress: BackfillRepo,
+    client: NasaClient,
+    nasa_key: String,
+    config: BackfillConfig,
+}
+
+fn bucket_start(date: NaiveDate, bucket: StatsBucket) -> NaiveDate {
+    match bucket {
+        StatsBucket::Day => date,
+        StatsBucket::Month => date.with_day(1).unwrap_or(date),
+    }
+}
+
+fn next_bucket(date: NaiveDate, bucket: StatsBucket) -> NaiveDate {
+    match bucket {
+        StatsBucket::Day => date + Days::new(1),
+        StatsBucket::Month => date + Months::new(1),
+    }
+}
+
+fn empty_counts() -> BTreeMap<char, u64> {
+    FLARE_CLASSES.iter().map(|c| (*c, 0)).collect()
+}
+
+fn flare_class(e: &SpaceWeatherEvent) -> Option<(String, FlareClass)> {
+    let class_type = e
+        .details
+        .get("class_type")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .or_else(|| e.summary.clone())?;
+    FlareClass::parse(&class_type).map(|c| (class_type, c))
+}
+
+fn summarize(e: &SpaceWeatherEvent, class_type: String, class: &FlareClass) -> FlareSummary {
+    FlareSummary {
+        activity_id: e.activity_id.clone(),
+        class_type,
+        flux: class.flux(),
+        begin_time: e.start_time,
+        peak_time: e
+            .details
+            .get("peak_time")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        source_location: e.details.get("source_location").and_then(|v| v.as_str()).map(str::to_string),
+        active_region_num: e.details.get("active_region_num").and_then(|v| v.as_i64()),
+    }
+}
+
+impl FlareService {
+    pub fn new(
+        repo: SpaceWeatherRepo,
+        progress: BackfillRepo,
+        client: NasaClient,
+        nasa_key: String,
+        config: &BackfillConfig,
+    ) -> Self {
+        Self {
+            repo,
+            progress,
+            client,
+            nasa_key,
+            config: config.clone(),
+        }
+    }
+
+    // По умолчанию последние 30 дней; шаг — сутки до трёх месяцев и месяц для длинных окон
+    pub async fn stats(
+        &self,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        bucket: Option<StatsBucket>,
+    ) -> Result<FlareStats, ApiError> {
+        let to = to.unwrap_or_else(|| Utc::now().date_naive());
+        let from = from.unwrap_or(to - Days::new(30));
+        if from > to {
+            return Err(ApiError::Validation("from must not be after to".to_string()));
+        }
+        let span = (to - from).num_days();
+        if span > MAX_STATS_DAYS {
+            return Err(ApiError::Validation(format!("window is limited to {} days", MAX_STATS_DAYS)));
+        }
+        let bucket = bucket.unwrap_or(if span <= DAILY_MAX_DAYS { StatsBucket::Day } else { StatsBucket::Month });
+
+        let events = self
+            .repo
+            .list(
+                "flr",
+                from.and_time(NaiveTime::MIN).and_utc(),
+                (to + Days::new(1)).and_time(NaiveTime::MIN).and_utc(),
+            )
+            .await?;
+
+        // Пустые периоды тоже отдаются — на графике это нули, а не разрывы
+        let mut buckets: BTreeMap<NaiveDate, FlareBucket> = BTreeMap::new();
+        let mut period = bucket_start(from, bucket);
+        while period <= to {
+            buckets.insert(
+                period,
+                FlareBucket {
+                    period,
+                    total: 0,
+                    counts: empty_counts(),
+                    peak_class: None,
+                    peak_flux: None,
+                },
+            );
+            period = next_bucket(period, bucket);
+        }
+
+        let mut by_class = empty_counts();
+        let mut strongest: Option<FlareSummary> = None;
+        let mut unclassified = 0u64;
+        let mut total = 0u64;
+
+        for e in &events {
+            let Some((class_type, class)) = flare_class(e) else {
+                unclassified += 1;
+                continue;
+            };
+            total += 1;
+            *by_class.entry(class.letter).or_default() += 1;
+
+            if let Some(b) = buckets.get_mut(&bucket_start(e.start_time.date_naive(), bucket)) {
+                b.total += 1;
+                *b.counts.entry(class.letter).or_default() += 1;
+                if b.peak_flux.is_none_or(|f| class.flux() > f) {
+                    b.peak_flux = Some(class.flux());
+                    b.peak_class = Some(class.label());
+                }
+            }
+
+            if strongest.as_ref().is_none_or(|s| class.flux() > s.flux) {
+                strongest = Some(summarize(e, class_type, &class));
+            }
+        }
+
+        Ok(FlareStats {
+            from,
+            to,
+            bucket,
+            total,
+            by_class,
+            buckets: buckets.into_values().collect(),
+            strongest,
+            unclassified,
+        })
+    }
+
+    // Догрузка каталога вспышек окнами от FLR_BACKFILL_FROM, свежие окна первыми.
+    // Закрытые окна отмечаются и больше не запрашиваются. Незакрытые (не больше двух) перечитываются
+    // каждый запуск сверх chunks_per_run — иначе они занимали бы весь бюджет и история стояла бы
+    pub async fn backfill(&self) -> Result<u64, ApiError> {
+        let today = Utc::now().date_naive();
+        let settled = today - Days::new(SETTLE_DAYS);
+        let done = self.progress.done(BACKFILL_JOB, self.config.flr_from, today).await?;
+
+        let mut open = Vec::new();
+        let mut closed = Vec::new();
+        let mut start = self.config.flr_from;
+        while start <= today {
+            let end = (start + Days::new(self.config.chunk_days - 1)).min(today);
+            if end >= settled {
+                open.push((start, end));
+            } else if done.binary_search(&start).is_err() {
+                closed.push((start, end));
+            }
+            start = end + Days::new(1);
+        }
+        closed.reverse();
+
+        // Ошибка одного окна не останавливает остальные: иначе окно, которое
+        // стабильно падает, навсегда закрыло бы путь к более старой истории
+        let mut written = 0u64;
+        for (start, end) in open {
+            match self.backfill_window(start, end).await {
+                Ok((upserted, _)) => written += upserted,
+                Err(e) => tracing::warn!("flare backfill {}..{} failed: {:?}", start, end, e),
+            }
+        }
+        for (start, end) in closed.into_iter().take(self.config.chunks_per_run as usize) {
+            match self.backfill_window(start, end).await {
+                Ok((upserted, records)) => {
+                    written += upserted;
+                    self.progress.mark_done(BACKFILL_JOB, start, records).await?;
+                }
+                Err(e) => tracing::warn!("flare backfill {}..{} failed: {:?}", start, end, e),
+            }
+        }
+
+        Ok(written)
+    }
+
+    async fn backfill_window(&self, start: NaiveDate, end: NaiveDate) -> Result<(u64, i64), ApiError> {
+        let payload = self.client.fetch_donki_flr_range(&self.nasa_key, start, end).await?;
+        let (parsed, _) = SpacePayload::parse("flr", &payload)
+            .map_err(|e| ApiError::ExternalApi(format!("DONKI FLR {}..{}: {}", start, end, e)))?;
+        let events = parsed.weather_events();
+        let records = events.len() as i64;
+        let written = self.repo.upsert(&events).await?;
+        tracing::info!("flare backfill {}..{}: {} flares", start, end, records);
+        Ok((written, records))
+    }
+}
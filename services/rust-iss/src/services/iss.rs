@@ -1,21 +1,51 @@
+use std::time::Duration;
+
 use serde_json::Value;
 
 use crate::clients::iss::{IssClient, IssClientTrait};
 use crate::domain::{ApiError, IssRecord, Trend};
 use crate::repo::iss::{IssRepo, IssRepository};
+use crate::repo::throttle::{ThrottleRepo, ThrottleRepository};
+use crate::repo::ReadCache;
+use crate::services::SingleFlight;
+
+const LAST_KEY: &str = "iss:last";
+const TRIGGER_KEY: &str = "iss:fetch";
 
 pub struct IssService {
     repo: IssRepo,
     client: IssClient,
+    cache: ReadCache,
+    last_ttl: Duration,
+    throttle: ThrottleRepo,
+    min_interval: u64,
+    flights: SingleFlight<bool>,
 }
 
 impl IssService {
-    pub fn new(repo: IssRepo, client: IssClient) -> Self {
-        Self { repo, client }
+    pub fn new(
+        repo: IssRepo,
+        client: IssClient,
+        cache: ReadCache,
+        last_ttl: Duration,
+        throttle: ThrottleRepo,
+        min_interval: u64,
+    ) -> Self {
+        Self {
+            repo,
+            client,
+            cache,
+            last_ttl,
+            throttle,
+            min_interval,
+            flights: SingleFlight::default(),
+        }
     }
 
     pub async fn get_last(&self) -> Result<Option<IssRecord>, ApiError> {
-        self.repo.get_last().await
+        self.cache
+            .get_or_load(LAST_KEY, self.last_ttl, || self.repo.get_last())
+            .await
     }
 
     pub async fn fetch_and_store(&self, url: &str) -> Result<(), ApiError> {
@@ -25,7 +55,31 @@ impl IssService {
         crate::domain::validation::validate_iss_payload(&payload)
             .map_err(|e| ApiError::Validation(format!("ISS payload validation failed: {:?}", e)))?;
         
-        self.repo.insert(url, payload).await
+        self.repo.insert(url, payload).await?;
+        self.cache.invalidate(LAST_KEY).await;
+        Ok(())
+    }
+
+    // Ручной запуск: одновременные вызовы делят один запрос к апстриму,
+    // а чаще min_interval (на все инстансы) апстрим не дёргается вовсе.
+    // Возвращает false, если обновление пропущено из-за интервала.
+    pub async fn trigger_fetch(&self, url: &str) -> Result<bool, ApiError> {
+        self.flights
+            .run(TRIGGER_KEY, || async {
+                if !self.throttle.try_acquire(TRIGGER_KEY, self.min_interval).await? {
+                    tracing::debug!("manual ISS fetch throttled");
+                    return Ok(false);
+                }
+                if let Err(e) = self.fetch_and_store(url).await {
+                    // Интервал отсчитывается от успешных запусков, а не от попыток
+                    if let Err(release_err) = self.throttle.release(TRIGGER_KEY).await {
+                        tracing::warn!("ISS throttle release failed: {:?}", release_err);
+                    }
+                    return Err(e);
+                }
+                Ok(true)
+            })
+            .await
     }
 
     pub async fn calculate_trend(&self) -> Result<Trend, ApiError> {
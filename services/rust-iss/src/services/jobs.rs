@@ -0,0 +1,70 @@
+use serde_json::Value;
+
+use crate::config::JobQueueConfig;
+use crate::domain::{ApiError, Job, JobStatus, NewJob};
+use crate::repo::jobs::{JobRepo, JobRepository};
+
+pub struct JobService {
+    repo: JobRepo,
+    config: JobQueueConfig,
+}
+
+impl JobService {
+    pub fn new(repo: JobRepo, config: JobQueueConfig) -> Self {
+        Self { repo, config }
+    }
+
+    pub async fn enqueue(&self, job: NewJob) -> Result<i64, ApiError> {
+        self.repo.enqueue(&job, self.config.max_attempts as i32).await
+    }
+
+    pub async fn get(&self, id: i64) -> Result<Job, ApiError> {
+        self.repo
+            .get(id)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Job {} not found", id)))
+    }
+
+    pub async fn list(&self, status: Option<JobStatus>, limit: i64) -> Result<Vec<Job>, ApiError> {
+        self.repo.list(status, limit.clamp(1, 500)).await
+    }
+
+    pub async fn claim_next(&self, worker_id: &str) -> Result<Option<Job>, ApiError> {
+        self.repo.claim_next(worker_id).await
+    }
+
+    pub async fn complete(&self, job: &Job, result: Value) -> Result<(), ApiError> {
+        self.repo.complete(job.id, result).await
+    }
+
+    pub async fn fail(&self, job: &Job, error: &str) -> Result<JobStatus, ApiError> {
+        // Экспоненциальная задержка между попытками
+        let exp = (job.attempts.max(1) - 1).min(16) as u32;
+        let delay = self
+            .config
+            .retry_base_seconds
+            .saturating_mul(2u64.saturating_pow(exp))
+            .min(self.config.retry_max_seconds);
+        self.repo.fail(job.id, error, delay as i64).await
+    }
+
+    pub async fn renew(&self, job: &Job, worker_id: &str) -> Result<bool, ApiError> {
+        self.repo.renew(job.id, worker_id).await
+    }
+
+    // Аренду продлеваем втрое чаще, чем она истекает
+    pub fn heartbeat_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs((self.config.lease_seconds / 3).max(1))
+    }
+
+    pub async fn requeue_stale(&self) -> Result<u64, ApiError> {
+        self.repo.requeue_stale(self.config.lease_seconds as i64).await
+    }
+
+    pub async fn release(&self, worker_ids: &[String]) -> Result<u64, ApiError> {
+        if worker_ids.is_empty() {
+            return Ok(0);
+        }
+        self.repo.release(worker_ids).await
+    }
+}
@@ -0,0 +1,87 @@
+use crate::clients::jwst::{JwstClient, JwstClientTrait};
+use crate::config::JwstConfig;
+use crate::domain::{ApiError, JwstFeed, JwstFilter};
+use crate::repo::jwst::{JwstRepo, JwstRepository};
+
+pub struct JwstService {
+    repo: JwstRepo,
+    client: JwstClient,
+    program_id: Option<String>,
+    sync_pages: u32,
+    per_page: u32,
+}
+
+impl JwstService {
+    pub fn new(repo: JwstRepo, client: JwstClient, config: &JwstConfig) -> Self {
+        Self {
+            repo,
+            client,
+            program_id: config.program_id.clone(),
+            sync_pages: config.sync_pages.max(1),
+            per_page: config.per_page.clamp(1, 100),
+        }
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.client.is_configured()
+    }
+
+    // Лента из кэша в Postgres; апстрим опрашивается только планировщиком
+    pub async fn feed(&self, filter: &JwstFilter, page: i64, per_page: i64) -> Result<JwstFeed, ApiError> {
+        let page = page.max(1);
+        let per_page = per_page.clamp(1, 100);
+        let offset = (page - 1)
+            .checked_mul(per_page)
+            .ok_or_else(|| ApiError::Validation(format!("page {} is out of range", page)))?;
+        let items = self.repo.list(filter, per_page, offset).await?;
+        let total = self.repo.count(filter).await?;
+
+        Ok(JwstFeed {
+            page,
+            per_page,
+            total,
+            items,
+        })
+    }
+
+    // Общая лента jpg и, если задан JWST_PROGRAM_ID, наблюдения программы
+    pub async fn sync(&self) -> Result<usize, ApiError> {
+        let mut filters = vec![JwstFilter::default()];
+        if let Some(program) = &self.program_id {
+            filters.push(JwstFilter {
+                program: Some(program.clone()),
+                ..Default::default()
+            });
+        }
+
+        let mut written = 0usize;
+        for filter in &filters {
+            for page in 1..=self.sync_pages {
+                let observations = self.client.fetch_observations(filter, page, self.per_page).await?;
+                let last_page = observations.len() < self.per_page as usize;
+
+                for obs in &observations {
+                    if let Some(image) = obs.normalize() {
+                        let raw = serde_json::json!({
+                            "id": obs.id,
+                            "observation_id": obs.observation_id,
+                            "program": obs.program,
+                            "file_type": obs.file_type,
+                            "location": obs.location,
+                            "thumbnail": obs.thumbnail,
+                            "mission": obs.details.mission,
+                        });
+                        self.repo.upsert(&image, &raw).await?;
+                        written += 1;
+                    }
+                }
+
+                if last_page {
+                    break;
+                }
+            }
+        }
+
+        Ok(written)
+    }
+}
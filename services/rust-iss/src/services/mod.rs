@@ -1,10 +1,26 @@
+pub mod apod;
+pub mod astro;
+pub mod flares;
 pub mod iss;
 pub mod osdr;
 pub mod space;
+pub mod space_weather;
+pub mod jobs;
+pub mod jwst;
+pub mod neo;
+pub mod single_flight;
 
+pub use apod::ApodService;
+pub use astro::AstroService;
+pub use flares::FlareService;
 pub use iss::IssService;
 pub use osdr::OsdrService;
 pub use space::SpaceService;
+pub use space_weather::SpaceWeatherService;
+pub use jobs::JobService;
+pub use jwst::JwstService;
+pub use neo::NeoService;
+pub use single_flight::SingleFlight;
 
 
 
@@ -0,0 +1,190 @@
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use serde_json::Value;
+
+use crate::clients::nasa::{NasaClient, NasaClientTrait};
+use crate::config::NeoConfig;
+use crate::domain::{
+    parse_one, ApiError, Fetched, NeoApproachFilter, NeoApproaches, NeoFeed, NeoLookup, NeoObjectRecord, NeoRanking,
+    SpacePayload,
+};
+use crate::repo::cache::{CacheRepo, CacheRepository};
+use crate::repo::neo::{NeoRepo, NeoRepository};
+
+const MAX_WINDOW_DAYS: i64 = 366;
+
+pub struct NeoService {
+    repo: NeoRepo,
+    cache_repo: CacheRepo,
+    client: NasaClient,
+    nasa_key: String,
+    ahead_days: u64,
+    object_ttl: u64,
+    refresh_batch: u32,
+}
+
+fn parse_lookup(payload: &Value) -> Result<NeoLookup, ApiError> {
+    parse_one::<NeoLookup>(payload)
+        .map(|(lookup, _)| lookup)
+        .map_err(|e| ApiError::ExternalApi(format!("NeoWs lookup validation failed: {}", e)))
+}
+
+impl NeoService {
+    pub fn new(repo: NeoRepo, cache_repo: CacheRepo, client: NasaClient, nasa_key: String, config: &NeoConfig) -> Self {
+        Self {
+            repo,
+            cache_repo,
+            client,
+            nasa_key,
+            ahead_days: config.ahead_days.clamp(1, 7),
+            object_ttl: config.object_ttl.max(60),
+            refresh_batch: config.refresh_batch.max(1),
+        }
+    }
+
+    // Календарь наполняется из двух мест: из снимка ленты neo (прошедшие дни)
+    // и из отдельного запроса на ahead_days вперёд — его в space_cache нет
+    pub async fn sync(&self) -> Result<u64, ApiError> {
+        let mut written = 0u64;
+
+        if let Some(entry) = self.cache_repo.get_latest("neo").await? {
+            if let Ok((SpacePayload::Neo(feed), _)) = SpacePayload::parse("neo", &entry.payload) {
+                written += self.repo.upsert_approaches(&feed.approaches()).await?;
+            }
+        }
+
+        let today = Utc::now().date_naive();
+        let fetched = self
+            .client
+            .fetch_neo_feed(&self.nasa_key, today, today + Days::new(self.ahead_days))
+            .await?;
+        if let Fetched::Fresh(payload) = fetched {
+            let (feed, _) = parse_one::<NeoFeed>(&payload)
+                .map_err(|e| ApiError::Validation(format!("NeoWs validation failed: {}", e)))?;
+            written += self.repo.upsert_approaches(&feed.approaches()).await?;
+        }
+
+        Ok(written)
+    }
+
+    pub async fn approaches(&self, filter: NeoApproachFilter, limit: i64) -> Result<NeoApproaches, ApiError> {
+        if filter.from > filter.to {
+            return Err(ApiError::Validation("from must not be after to".to_string()));
+        }
+        if (filter.to - filter.from).num_days() > MAX_WINDOW_DAYS {
+            return Err(ApiError::Validation(format!("window is limited to {} days", MAX_WINDOW_DAYS)));
+        }
+        if filter.max_distance_ld.is_some_and(|d| d.is_nan() || d <= 0.0) {
+            return Err(ApiError::Validation("max_distance_ld must be positive".to_string()));
+        }
+
+        let items = self.repo.approaches(&filter, limit.clamp(1, 1000)).await?;
+        Ok(NeoApproaches {
+            from: filter.from,
+            to: filter.to,
+            count: items.len(),
+            items,
+        })
+    }
+
+    // Предстоящие сближения с Землёй: ближайшие по расстоянию и крупнейшие по диаметру.
+    // Полная лента есть только на ahead_days вперёд; дальше — лишь объекты, открытые через
+    // /neo/{id}, поэтому такое окно помечается как неполное
+    pub async fn ranking(&self, days: Option<u64>, limit: i64) -> Result<NeoRanking, ApiError> {
+        let days = days.unwrap_or(self.ahead_days);
+        if !(1..=MAX_WINDOW_DAYS as u64).contains(&days) {
+            return Err(ApiError::Validation(format!("days must be in [1, {}]", MAX_WINDOW_DAYS)));
+        }
+        let now = Utc::now();
+        let to: NaiveDate = now.date_naive() + Days::new(days);
+        let limit = limit.clamp(1, 100);
+
+        Ok(NeoRanking {
+            from: now,
+            to,
+            complete: days <= self.ahead_days,
+            closest: self.repo.closest(now, to, limit).await?,
+            largest: self.repo.largest(now, to, limit).await?,
+        })
+    }
+
+    // Карточка объекта: свежая копия из neo_objects, иначе lookup в NeoWs.
+    // Если апстрим недоступен, а копия есть — отдаём её с пометкой stale
+    pub async fn object(&self, id: &str) -> Result<NeoObjectRecord, ApiError> {
+        if id.is_empty() || id.len() > 20 || !id.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ApiError::Validation(format!("invalid NEO id: {}", id)));
+        }
+
+        let cached = self.repo.get_object(id).await?;
+        if let Some((payload, fetched_at)) = &cached {
+            let age = (Utc::now() - *fetched_at).num_seconds();
+            if age >= 0 && (age as u64) < self.object_ttl {
+                return Self::record(id, payload, *fetched_at, false);
+            }
+        }
+
+        match self.fetch_object(id).await {
+            Ok((lookup, fetched_at)) => Ok(NeoObjectRecord {
+                id: id.to_string(),
+                fetched_at,
+                stale: false,
+                object: lookup,
+            }),
+            Err(ApiError::NotFound(msg)) => {
+                if cached.is_some() {
+                    self.repo.delete_object(id).await?;
+                }
+                Err(ApiError::NotFound(msg))
+            }
+            Err(e) => match cached {
+                Some((payload, fetched_at)) => {
+                    tracing::warn!("neo lookup {}: serving stale copy: {}", id, e);
+                    Self::record(id, &payload, fetched_at, true)
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    // Обновляет самые старые из устаревших карточек, не больше refresh_batch за запуск
+    pub async fn refresh_objects(&self) -> Result<u64, ApiError> {
+        let ids = self.repo.stale_objects(self.object_ttl, self.refresh_batch as i64).await?;
+        let mut refreshed = 0u64;
+        for id in ids {
+            match self.fetch_object(&id).await {
+                Ok(_) => refreshed += 1,
+                // Объект убран из NeoWs — иначе он запрашивался бы каждый запуск впустую
+                Err(ApiError::NotFound(_)) => {
+                    tracing::info!("neo object {} is gone upstream, dropping", id);
+                    self.repo.delete_object(&id).await?;
+                }
+                Err(e) => tracing::warn!("neo object refresh {}: {}", id, e),
+            }
+        }
+        Ok(refreshed)
+    }
+
+    async fn fetch_object(&self, id: &str) -> Result<(NeoLookup, DateTime<Utc>), ApiError> {
+        let payload = self.client.fetch_neo_lookup(&self.nasa_key, id).await?;
+        let lookup = parse_lookup(&payload)?;
+
+        self.repo
+            .upsert_object(id, &lookup.object.name, lookup.object.is_potentially_hazardous_asteroid, &payload)
+            .await?;
+        // В lookup вся история сближений — заодно пополняем календарь
+        self.repo.upsert_approaches(&lookup.object.approaches()).await?;
+
+        Ok((lookup, Utc::now()))
+    }
+
+    // Сохранённый payload уже прошёл разбор; если теперь не читается — ошибка на нашей стороне
+    fn record(id: &str, payload: &Value, fetched_at: DateTime<Utc>, stale: bool) -> Result<NeoObjectRecord, ApiError> {
+        let (object, _) = parse_one::<NeoLookup>(payload)
+            .map_err(|e| ApiError::Internal(format!("stored NEO {} is unreadable: {}", id, e)))?;
+        Ok(NeoObjectRecord {
+            id: id.to_string(),
+            fetched_at,
+            stale,
+            object,
+        })
+    }
+}
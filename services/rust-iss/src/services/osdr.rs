@@ -1,28 +1,47 @@
+use std::time::Duration;
+
 use serde_json::Value;
 
 use crate::clients::nasa::{NasaClient, NasaClientTrait};
 use crate::domain::{ApiError, OsdrItem};
 use crate::repo::osdr::{OsdrRepo, OsdrRepository};
+use crate::repo::ReadCache;
+
+const LIST_KEY_PREFIX: &str = "osdr:list:";
 
 pub struct OsdrService {
     repo: OsdrRepo,
     client: NasaClient,
     nasa_url: String,
     nasa_key: String,
+    cache: ReadCache,
+    list_ttl: Duration,
 }
 
 impl OsdrService {
-    pub fn new(repo: OsdrRepo, client: NasaClient, nasa_url: String, nasa_key: String) -> Self {
+    pub fn new(
+        repo: OsdrRepo,
+        client: NasaClient,
+        nasa_url: String,
+        nasa_key: String,
+        cache: ReadCache,
+        list_ttl: Duration,
+    ) -> Self {
         Self {
             repo,
             client,
             nasa_url,
             nasa_key,
+            cache,
+            list_ttl,
         }
     }
 
     pub async fn list(&self, limit: i64) -> Result<Vec<OsdrItem>, ApiError> {
-        self.repo.list(limit).await
+        let key = format!("{}{}", LIST_KEY_PREFIX, limit);
+        self.cache
+            .get_or_load(&key, self.list_ttl, || self.repo.list(limit))
+            .await
     }
 
     pub async fn sync(&self) -> Result<usize, ApiError> {
@@ -45,6 +64,8 @@ impl OsdrService {
             written += 1;
         }
 
+        self.cache.invalidate_prefix(LIST_KEY_PREFIX).await;
+        self.cache.invalidate(crate::services::space::SUMMARY_KEY).await;
         Ok(written)
     }
 
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+
+use tokio::sync::broadcast;
+
+use crate::domain::ApiError;
+
+type Flights<T> = Mutex<HashMap<String, broadcast::Sender<Result<T, String>>>>;
+
+// Одновременные вызовы с одинаковым ключом выполняют один запрос к апстриму
+// и получают его результат. Первый вызвавший получает исходную ошибку,
+// остальные — её текст как ApiError::ExternalApi.
+pub struct SingleFlight<T> {
+    inflight: Flights<T>,
+}
+
+impl<T: Clone> Default for SingleFlight<T> {
+    fn default() -> Self {
+        Self {
+            inflight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T: Clone> SingleFlight<T> {
+    pub async fn run<F, Fut>(&self, key: &str, f: F) -> Result<T, ApiError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, ApiError>>,
+    {
+        let mut f = Some(f);
+        loop {
+            let follower = {
+                let mut inflight = self.inflight.lock().unwrap_or_else(|e| e.into_inner());
+                match inflight.get(key) {
+                    Some(tx) => Some(tx.subscribe()),
+                    None => {
+                        let (tx, _) = broadcast::channel(1);
+                        inflight.insert(key.to_string(), tx);
+                        None
+                    }
+                }
+            };
+
+            match follower {
+                Some(mut rx) => match rx.recv().await {
+                    Ok(Ok(v)) => return Ok(v),
+                    Ok(Err(msg)) => return Err(ApiError::ExternalApi(msg)),
+                    // Ведущий запрос отменили — пробуем стать ведущим сами
+                    Err(_) => continue,
+                },
+                None => {
+                    let mut flight = Flight {
+                        inflight: &self.inflight,
+                        key,
+                        done: false,
+                    };
+                    let leader = f.take().expect("single flight leader runs once");
+                    let result = leader().await;
+                    if let Some(tx) = flight.finish() {
+                        let _ = tx.send(result.as_ref().map(Clone::clone).map_err(|e| e.to_string()));
+                    }
+                    return result;
+                }
+            }
+        }
+    }
+}
+
+// Снимает ключ и при отмене ведущего, чтобы ожидающие не зависли
+struct Flight<'a, T> {
+    inflight: &'a Flights<T>,
+    key: &'a str,
+    done: bool,
+}
+
+impl<T> Flight<'_, T> {
+    fn finish(&mut self) -> Option<broadcast::Sender<Result<T, String>>> {
+        self.done = true;
+        self.inflight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(self.key)
+    }
+}
+
+impl<T> Drop for Flight<'_, T> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.inflight
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(self.key);
+        }
+    }
+}
@@ -1,15 +1,56 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Days, Utc};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::clients::nasa::{NasaClient, NasaClientTrait};
 use crate::clients::spacex::{SpaceXClient, SpaceXClientTrait};
-use crate::domain::{ApiError, SpaceSummary};
-use crate::repo::cache::{CacheRepo, CacheRepository};
+use crate::config::{Config, RetentionPolicy};
+use crate::domain::{
+    ApiError, Fetched, PruneReport, RefreshReport, RefreshStatus, SourceRefresh, SpaceCacheEntry, SpaceHistory, SpacePayload,
+    SpaceSummary, TypedSpaceEntry,
+};
+use crate::repo::cache::{CacheEntry, CacheRepo, CacheRepository, HistoryQuery};
+use crate::repo::throttle::{ThrottleRepo, ThrottleRepository};
+use crate::repo::ReadCache;
+use crate::services::SingleFlight;
+
+pub const SPACE_SOURCES: &[&str] = &["apod", "neo", "flr", "cme", "gst", "sep", "ips", "hss", "spacex"];
+pub const DONKI_SOURCES: &[&str] = &["flr", "cme", "gst", "sep", "ips", "hss"];
+
+// Вспышки и CME идут десятками в сутки, остальные события DONKI редки —
+// для них окно шире, чтобы на дашборде была последняя буря или поток.
+// Медленный CME идёт до Земли до недели, поэтому окно CME длиннее, чем у вспышек
+const DONKI_FLR_DAYS: u64 = 5;
+const DONKI_CME_DAYS: u64 = 7;
+const DONKI_EVENT_DAYS: u64 = 30;
+pub const SUMMARY_KEY: &str = "space:summary";
+
+// Строки БД для сводки. В кэш чтения кладутся только они: возраст и stale
+// зависят от текущего времени и считаются при каждом ответе
+#[derive(Serialize, Deserialize)]
+struct SummaryRows {
+    latest: BTreeMap<String, Option<CacheEntry>>,
+    osdr_count: i64,
+}
 
 pub struct SpaceService {
     cache_repo: CacheRepo,
     nasa_client: NasaClient,
     spacex_client: SpaceXClient,
     nasa_key: String,
+    refresh_concurrency: usize,
+    retention: BTreeMap<String, RetentionPolicy>,
+    freshness: BTreeMap<String, u64>,
+    cache: ReadCache,
+    summary_ttl: Duration,
+    throttle: ThrottleRepo,
+    manual_min_interval: u64,
+    flights: SingleFlight<SourceRefresh>,
 }
 
 impl SpaceService {
@@ -17,121 +58,468 @@ impl SpaceService {
         cache_repo: CacheRepo,
         nasa_client: NasaClient,
         spacex_client: SpaceXClient,
-        nasa_key: String,
+        cache: ReadCache,
+        throttle: ThrottleRepo,
+        config: &Config,
     ) -> Self {
         Self {
             cache_repo,
             nasa_client,
             spacex_client,
-            nasa_key,
+            nasa_key: config.nasa_key.clone(),
+            refresh_concurrency: config.refresh_concurrency.max(1),
+            retention: config.retention.policies.clone(),
+            freshness: config.freshness.clone(),
+            cache,
+            summary_ttl: config.read_cache.summary_ttl,
+            throttle,
+            manual_min_interval: config.manual_refresh_min_interval,
+            flights: SingleFlight::default(),
         }
     }
 
-    pub async fn get_latest(&self, source: &str) -> Result<Value, ApiError> {
-        let entry = self.cache_repo.get_latest(source).await?;
-        Ok(entry
+    pub async fn get_latest(&self, source: &str, max_age: Option<u64>) -> Result<Value, ApiError> {
+        let entry = self.latest_within(source, max_age).await?;
+        Ok(self.render_latest(source, entry))
+    }
+
+    fn render_latest(&self, source: &str, entry: Option<CacheEntry>) -> Value {
+        entry
             .map(|e| {
+                let (age_seconds, stale) = self.freshness_of(source, &e);
                 serde_json::json!({
                     "source": source,
                     "fetched_at": e.fetched_at,
+                    "last_seen_at": e.last_seen_at,
+                    "age_seconds": age_seconds,
+                    "stale": stale,
                     "payload": e.payload
                 })
             })
-            .unwrap_or_else(|| serde_json::json!({ "source": source, "message": "no data" })))
+            .unwrap_or_else(|| serde_json::json!({ "source": source, "message": "no data" }))
     }
 
-    pub async fn refresh(&self, sources: &[&str]) -> Result<Vec<String>, ApiError> {
-        let mut done = Vec::new();
+    // Возраст считается от last_seen_at — момента, когда апстрим последний раз подтвердил содержимое
+    fn freshness_of(&self, source: &str, entry: &CacheEntry) -> (i64, bool) {
+        let age = (Utc::now() - entry.last_seen_at).num_seconds().max(0);
+        let stale = self
+            .freshness
+            .get(source)
+            .map(|window| age as u64 > *window)
+            .unwrap_or(false);
+        (age, stale)
+    }
 
-        for src in sources {
-            match *src {
-                "apod" => {
-                    if self.fetch_apod().await.is_ok() {
-                        done.push("apod".to_string());
-                    }
-                }
-                "neo" => {
-                    if self.fetch_neo().await.is_ok() {
-                        done.push("neo".to_string());
-                    }
+    // С max_age кэш старше заданного возраста обновляется синхронно;
+    // если обновить не удалось — 503, а не устаревшие данные
+    async fn latest_within(&self, source: &str, max_age: Option<u64>) -> Result<Option<CacheEntry>, ApiError> {
+        let entry = self.cache_repo.get_latest(source).await?;
+        let Some(max_age) = max_age else {
+            return Ok(entry);
+        };
+        if !SPACE_SOURCES.contains(&source) {
+            return Err(ApiError::Validation(format!("{} cannot be refreshed on demand, max_age is not supported", source)));
+        }
+
+        let age = entry.as_ref().map(|e| self.freshness_of(source, e).0 as u64);
+        if matches!(age, Some(a) if a <= max_age) {
+            return Ok(entry);
+        }
+
+        let outcome = self.refresh_one(source).await;
+        if let Some(err) = outcome.error {
+            return Err(ApiError::ServiceUnavailable(format!(
+                "{} cache is older than {}s and refresh failed: {}",
+                source, max_age, err
+            )));
+        }
+
+        self.cache_repo.get_latest(source).await
+    }
+
+    pub async fn get_at(&self, source: &str, at: DateTime<Utc>) -> Result<SpaceCacheEntry, ApiError> {
+        self.cache_repo
+            .get_at(source, at)
+            .await?
+            .map(|e| to_entry(source, e))
+            .ok_or_else(|| ApiError::NotFound(format!("no {} snapshot at {}", source, at)))
+    }
+
+    pub async fn history(
+        &self,
+        source: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        limit: i64,
+        cursor: Option<&str>,
+    ) -> Result<SpaceHistory, ApiError> {
+        let before_id = match cursor {
+            Some(c) => Some(
+                c.parse::<i64>()
+                    .map_err(|_| ApiError::Validation(format!("invalid cursor: {}", c)))?,
+            ),
+            None => None,
+        };
+        let limit = limit.clamp(1, 200);
+
+        let rows = self
+            .cache_repo
+            .history(&HistoryQuery {
+                source: source.to_string(),
+                from,
+                to,
+                before_id,
+                limit,
+            })
+            .await?;
+
+        // Полная страница — возможно, есть следующая
+        let next_cursor = if rows.len() as i64 == limit {
+            rows.last().map(|e| e.id.to_string())
+        } else {
+            None
+        };
+
+        Ok(SpaceHistory {
+            source: source.to_string(),
+            items: rows.into_iter().map(|e| to_entry(source, e)).collect(),
+            next_cursor,
+        })
+    }
+
+    pub async fn get_typed(&self, source: &str, max_age: Option<u64>) -> Result<TypedSpaceEntry, ApiError> {
+        if !SPACE_SOURCES.contains(&source) {
+            return Err(ApiError::Validation(format!("no typed model for source {}", source)));
+        }
+        let entry = self
+            .latest_within(source, max_age)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("no data for {}", source)))?;
+        let (age_seconds, stale) = self.freshness_of(source, &entry);
+
+        // Снимок уже прошёл проверку при записи; расхождение с моделью — ошибка на нашей стороне
+        let (payload, report) = SpacePayload::parse(source, &entry.payload)
+            .map_err(|e| ApiError::Internal(format!("{} payload does not match model: {}", source, e)))?;
+
+        Ok(TypedSpaceEntry {
+            fetched_at: entry.fetched_at,
+            last_seen_at: entry.last_seen_at,
+            age_seconds,
+            stale,
+            payload,
+            report,
+        })
+    }
+
+    // Источники опрашиваются параллельно, не более refresh_concurrency одновременно;
+    // ошибка одного источника не прерывает остальные
+    pub async fn refresh(&self, sources: &[&str]) -> Result<RefreshReport, ApiError> {
+        let pending: Vec<_> = sources.iter().map(|src| self.refresh_one(src)).collect();
+        let mut results: Vec<SourceRefresh> = stream::iter(pending)
+            .buffer_unordered(self.refresh_concurrency)
+            .collect()
+            .await;
+
+        // Порядок в отчёте совпадает с порядком запроса
+        results.sort_by_key(|r| sources.iter().position(|s| *s == r.source));
+
+        Ok(RefreshReport::new(results))
+    }
+
+    // Ручное обновление: источник, обновлённый любым инстансом раньше
+    // manual_min_interval, помечается throttled и апстрим не запрашивается
+    pub async fn refresh_manual(&self, sources: &[&str]) -> Result<RefreshReport, ApiError> {
+        let pending: Vec<_> = sources.iter().map(|src| self.refresh_manual_one(src)).collect();
+        let mut results = Vec::with_capacity(sources.len());
+        let mut outcomes = stream::iter(pending).buffer_unordered(self.refresh_concurrency);
+        while let Some(outcome) = outcomes.next().await {
+            results.push(outcome?);
+        }
+
+        results.sort_by_key(|r| sources.iter().position(|s| *s == r.source));
+
+        Ok(RefreshReport::new(results))
+    }
+
+    async fn refresh_manual_one(&self, source: &str) -> Result<SourceRefresh, ApiError> {
+        let key = format!("manual:{}", source);
+        self.flights
+            .run(&key, || async {
+                let throttle_key = format!("space:{}", source);
+                if !self.throttle.try_acquire(&throttle_key, self.manual_min_interval).await? {
+                    return Ok(SourceRefresh {
+                        source: source.to_string(),
+                        status: RefreshStatus::Throttled,
+                        duration_ms: 0,
+                        records: 0,
+                        error: None,
+                    });
                 }
-                "flr" => {
-                    if self.fetch_flr().await.is_ok() {
-                        done.push("flr".to_string());
+                let outcome = self.refresh_one(source).await;
+                if outcome.status == RefreshStatus::Error {
+                    if let Err(e) = self.throttle.release(&throttle_key).await {
+                        tracing::warn!("{} throttle release failed: {:?}", source, e);
                     }
                 }
-                "cme" => {
-                    if self.fetch_cme().await.is_ok() {
-                        done.push("cme".to_string());
-                    }
+                Ok(outcome)
+            })
+            .await
+    }
+
+    // Одновременные обновления одного источника (планировщик, max_age, ручные) делят один запрос
+    async fn refresh_one(&self, source: &str) -> SourceRefresh {
+        let outcome = self
+            .flights
+            .run(source, || async { Ok(self.refresh_uncoalesced(source).await) })
+            .await;
+
+        outcome.unwrap_or_else(|e| SourceRefresh {
+            source: source.to_string(),
+            status: RefreshStatus::Error,
+            duration_ms: 0,
+            records: 0,
+            error: Some(e.to_string()),
+        })
+    }
+
+    async fn refresh_uncoalesced(&self, source: &str) -> SourceRefresh {
+        let started = Instant::now();
+        let res = match source {
+            "apod" => self.fetch_apod().await,
+            "neo" => self.fetch_neo().await,
+            "flr" => {
+                let today = Utc::now().date_naive();
+                let start = today - Days::new(DONKI_FLR_DAYS);
+                self.fetch_donki("flr", || self.nasa_client.fetch_donki_flr(&self.nasa_key, start, today))
+                    .await
+            }
+            "cme" => {
+                self.fetch_donki("cme", || self.nasa_client.fetch_donki_cme(&self.nasa_key, DONKI_CME_DAYS))
+                    .await
+            }
+            "gst" => {
+                self.fetch_donki("gst", || self.nasa_client.fetch_donki_gst(&self.nasa_key, DONKI_EVENT_DAYS))
+                    .await
+            }
+            "sep" => {
+                self.fetch_donki("sep", || self.nasa_client.fetch_donki_sep(&self.nasa_key, DONKI_EVENT_DAYS))
+                    .await
+            }
+            "ips" => {
+                self.fetch_donki("ips", || self.nasa_client.fetch_donki_ips(&self.nasa_key, DONKI_EVENT_DAYS))
+                    .await
+            }
+            "hss" => {
+                self.fetch_donki("hss", || self.nasa_client.fetch_donki_hss(&self.nasa_key, DONKI_EVENT_DAYS))
+                    .await
+            }
+            "spacex" => self.fetch_spacex().await,
+            _ => Err(ApiError::Validation(format!("unknown source: {}", source))),
+        };
+        let duration_ms = started.elapsed().as_millis() as u64;
+
+        match res {
+            Ok(records) => SourceRefresh {
+                source: source.to_string(),
+                status: RefreshStatus::Ok,
+                duration_ms,
+                records,
+                error: None,
+            },
+            Err(e) => {
+                tracing::warn!("refresh {} failed: {}", source, e);
+                SourceRefresh {
+                    source: source.to_string(),
+                    status: RefreshStatus::Error,
+                    duration_ms,
+                    records: 0,
+                    error: Some(e.to_string()),
                 }
-                "spacex" => {
-                    if self.fetch_spacex().await.is_ok() {
-                        done.push("spacex".to_string());
+            }
+        }
+    }
+
+    // Удаляет снимки старше окна хранения источника; источники с KeepAll не трогает
+    pub async fn prune(&self) -> Result<PruneReport, ApiError> {
+        let mut report = PruneReport::default();
+
+        for (source, policy) in &self.retention {
+            match policy {
+                RetentionPolicy::KeepAll => report.kept_all.push(source.clone()),
+                RetentionPolicy::KeepDays(days) => {
+                    let removed = self.cache_repo.prune(source, *days).await?;
+                    if removed > 0 {
+                        tracing::info!("pruned {} {} snapshots older than {} days", removed, source, days);
                     }
+                    report.total_removed += removed;
+                    report.removed.insert(source.clone(), removed);
                 }
-                _ => {}
             }
         }
 
-        Ok(done)
+        Ok(report)
     }
 
     pub async fn get_summary(&self) -> Result<SpaceSummary, ApiError> {
-        let apod = self.get_latest("apod").await?;
-        let neo = self.get_latest("neo").await?;
-        let flr = self.get_latest("flr").await?;
-        let cme = self.get_latest("cme").await?;
-        let spacex = self.get_latest("spacex").await?;
-
-        // ISS получаем из кэша или пустой объект (игнорируем ошибки)
-        let iss = self.get_latest("iss").await.unwrap_or_else(|_| serde_json::json!({}));
-        let osdr_count = self.cache_repo.count_osdr().await?;
+        let mut rows: SummaryRows = self
+            .cache
+            .get_or_load(SUMMARY_KEY, self.summary_ttl, || self.load_summary_rows())
+            .await?;
+        let mut latest = |source: &str| self.render_latest(source, rows.latest.remove(source).flatten());
 
         Ok(SpaceSummary {
-            apod,
-            neo,
-            flr,
-            cme,
-            spacex,
-            iss,
-            osdr_count,
+            apod: latest("apod"),
+            neo: latest("neo"),
+            flr: latest("flr"),
+            cme: latest("cme"),
+            gst: latest("gst"),
+            sep: latest("sep"),
+            ips: latest("ips"),
+            hss: latest("hss"),
+            spacex: latest("spacex"),
+            iss: latest("iss"),
+            osdr_count: rows.osdr_count,
         })
     }
 
-    async fn fetch_apod(&self) -> Result<(), ApiError> {
-        let payload = self.nasa_client.fetch_apod(&self.nasa_key).await?;
-        crate::domain::validation::validate_space_cache_entry("apod", &payload)
-            .map_err(|e| ApiError::Validation(format!("APOD validation failed: {:?}", e)))?;
-        self.cache_repo.insert("apod", payload).await
+    async fn load_summary_rows(&self) -> Result<SummaryRows, ApiError> {
+        let mut latest = BTreeMap::new();
+        for source in SPACE_SOURCES {
+            latest.insert(source.to_string(), self.cache_repo.get_latest(source).await?);
+        }
+        // ISS в сводке необязательна — ошибку чтения не пробрасываем
+        latest.insert("iss".to_string(), self.cache_repo.get_latest("iss").await.unwrap_or(None));
+
+        Ok(SummaryRows {
+            latest,
+            osdr_count: self.cache_repo.count_osdr().await?,
+        })
     }
 
-    async fn fetch_neo(&self) -> Result<(), ApiError> {
-        let payload = self.nasa_client.fetch_neo_feed(&self.nasa_key, 2).await?;
-        crate::domain::validation::validate_space_cache_entry("neo", &payload)
-            .map_err(|e| ApiError::Validation(format!("NeoWs validation failed: {:?}", e)))?;
-        self.cache_repo.insert("neo", payload).await
+    // HTTP-клиент запоминает ETag/Last-Modified до того, как ответ проверен и сохранён.
+    // Отвергнутый ответ не должен потом подтверждаться 304 — иначе свежей станет старая копия
+    fn forget_validators(&self, source: &str) {
+        match source {
+            "spacex" => self.spacex_client.forget_validators(),
+            _ => self.nasa_client.forget_validators(source),
+        }
+    }
+
+    fn validate(&self, source: &str, payload: &Value) -> Result<(), String> {
+        let report = crate::domain::validation::validate_space_payload(source, payload)
+            .inspect_err(|_| self.forget_validators(source))?;
+        if !report.is_clean() {
+            tracing::warn!(
+                "{} payload drift: unknown={:?} missing={:?} rejected={}",
+                source,
+                report.unknown_fields,
+                report.missing_fields,
+                report.rejected
+            );
+        }
+        Ok(())
+    }
+
+    async fn store(&self, source: &str, payload: Value) -> Result<u64, ApiError> {
+        let inserted = self
+            .cache_repo
+            .insert(source, payload)
+            .await
+            .inspect_err(|_| self.forget_validators(source))?;
+        // last_seen_at меняется и без новой строки, поэтому сводку сбрасываем всегда
+        self.cache.invalidate(SUMMARY_KEY).await;
+        Ok(inserted as u64)
+    }
+
+    // 304: содержимое не изменилось — новой строки нет, только last_seen_at у последней.
+    // Если подтверждать нечего (кэш очищен), повторяем запрос без валидаторов.
+    async fn fetch_fresh<F, Fut>(&self, source: &str, fetch: F) -> Result<Option<Value>, ApiError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<Fetched, ApiError>>,
+    {
+        if let Fetched::Fresh(payload) = fetch().await? {
+            return Ok(Some(payload));
+        }
+
+        if self.cache_repo.touch_latest(source).await? {
+            tracing::debug!("{} not modified upstream", source);
+            self.cache.invalidate(SUMMARY_KEY).await;
+            return Ok(None);
+        }
+
+        self.forget_validators(source);
+        match fetch().await? {
+            Fetched::Fresh(payload) => Ok(Some(payload)),
+            Fetched::Unchanged => Err(ApiError::ExternalApi(format!(
+                "{} answered 304 to an unconditional request",
+                source
+            ))),
+        }
     }
 
-    async fn fetch_flr(&self) -> Result<(), ApiError> {
-        let payload = self.nasa_client.fetch_donki_flr(&self.nasa_key, 5).await?;
-        crate::domain::validation::validate_space_cache_entry("flr", &payload)
-            .map_err(|e| ApiError::Validation(format!("DONKI FLR validation failed: {:?}", e)))?;
-        self.cache_repo.insert("flr", payload).await
+    async fn fetch_apod(&self) -> Result<u64, ApiError> {
+        let Some(payload) = self
+            .fetch_fresh("apod", || self.nasa_client.fetch_apod(&self.nasa_key))
+            .await?
+        else {
+            return Ok(0);
+        };
+        self.validate("apod", &payload)
+            .map_err(|e| ApiError::Validation(format!("APOD validation failed: {:?}", e)))?;
+        self.store("apod", payload).await
     }
 
-    async fn fetch_cme(&self) -> Result<(), ApiError> {
-        let payload = self.nasa_client.fetch_donki_cme(&self.nasa_key, 5).await?;
-        crate::domain::validation::validate_space_cache_entry("cme", &payload)
-            .map_err(|e| ApiError::Validation(format!("DONKI CME validation failed: {:?}", e)))?;
-        self.cache_repo.insert("cme", payload).await
+    async fn fetch_neo(&self) -> Result<u64, ApiError> {
+        let Some(payload) = self
+            .fetch_fresh("neo", || {
+                let today = Utc::now().date_naive();
+                self.nasa_client.fetch_neo_feed(&self.nasa_key, today - Days::new(2), today)
+            })
+            .await?
+        else {
+            return Ok(0);
+        };
+        self.validate("neo", &payload)
+            .map_err(|e| ApiError::Validation(format!("NeoWs validation failed: {:?}", e)))?;
+        self.store("neo", payload).await
+    }
+
+    // Каждая лента DONKI хранится под своим ключом источника в space_cache
+    async fn fetch_donki<F, Fut>(&self, source: &str, fetch: F) -> Result<u64, ApiError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<Fetched, ApiError>>,
+    {
+        let Some(payload) = self.fetch_fresh(source, fetch).await? else {
+            return Ok(0);
+        };
+        self.validate(source, &payload)
+            .map_err(|e| {
+                ApiError::Validation(format!("DONKI {} validation failed: {:?}", source.to_uppercase(), e))
+            })?;
+        self.store(source, payload).await
     }
 
-    async fn fetch_spacex(&self) -> Result<(), ApiError> {
-        let payload = self.spacex_client.fetch_next_launch().await?;
-        crate::domain::validation::validate_space_cache_entry("spacex", &payload)
+    async fn fetch_spacex(&self) -> Result<u64, ApiError> {
+        let Some(payload) = self
+            .fetch_fresh("spacex", || self.spacex_client.fetch_next_launch())
+            .await?
+        else {
+            return Ok(0);
+        };
+        self.validate("spacex", &payload)
             .map_err(|e| ApiError::Validation(format!("SpaceX validation failed: {:?}", e)))?;
-        self.cache_repo.insert("spacex", payload).await
+        self.store("spacex", payload).await
     }
 }
 
+fn to_entry(source: &str, e: CacheEntry) -> SpaceCacheEntry {
+    SpaceCacheEntry {
+        id: e.id,
+        source: source.to_string(),
+        fetched_at: e.fetched_at,
+        last_seen_at: e.last_seen_at,
+        payload: e.payload,
+    }
+}
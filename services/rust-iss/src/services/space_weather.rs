@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, Utc};
+
+use crate::domain::{ApiError, CmeArrivals, SpacePayload, SpaceWeatherChain, SpaceWeatherNode};
+use crate::repo::cache::{CacheRepo, CacheRepository};
+use crate::repo::space_weather::{SpaceWeatherRepo, SpaceWeatherRepository};
+use crate::services::space::DONKI_SOURCES;
+
+// Цепочка вспышка → CME → волна → буря укладывается в 4–5 связей; запас на взаимодействие CME
+const MAX_CHAIN_DEPTH: i32 = 10;
+
+pub struct SpaceWeatherService {
+    cache_repo: CacheRepo,
+    repo: SpaceWeatherRepo,
+}
+
+impl SpaceWeatherService {
+    pub fn new(cache_repo: CacheRepo, repo: SpaceWeatherRepo) -> Self {
+        Self { cache_repo, repo }
+    }
+
+    // Разбирает последние снимки лент DONKI в таблицу событий. Берётся именно снимок,
+    // а не ответ апстрима: при 304 новой строки нет, но события всё равно должны попасть в граф
+    pub async fn ingest_latest(&self) -> Result<u64, ApiError> {
+        let mut written = 0u64;
+        for source in DONKI_SOURCES {
+            let Some(entry) = self.cache_repo.get_latest(source).await? else {
+                continue;
+            };
+            let events = match SpacePayload::parse(source, &entry.payload) {
+                Ok((payload, _)) => payload.weather_events(),
+                Err(e) => {
+                    tracing::warn!("space weather ingest: {} snapshot {} unreadable: {}", source, entry.id, e);
+                    continue;
+                }
+            };
+            written += self.repo.upsert(&events).await?;
+        }
+        if written > 0 {
+            tracing::info!("space weather ingest: {} events updated", written);
+        }
+        Ok(written)
+    }
+
+    pub async fn chain(&self, activity_id: &str) -> Result<SpaceWeatherChain, ApiError> {
+        let root = self
+            .repo
+            .find(&[activity_id.to_string()])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| ApiError::NotFound(format!("space weather event {} not found", activity_id)))?;
+
+        let (causes, effects) = self.repo.walk(activity_id, MAX_CHAIN_DEPTH).await?;
+
+        let mut ids: Vec<String> = causes.iter().chain(effects.iter()).map(|(id, _)| id.clone()).collect();
+        ids.push(root.activity_id.clone());
+        let known: HashMap<String, _> = self
+            .repo
+            .find(&ids)
+            .await?
+            .into_iter()
+            .map(|e| (e.activity_id.clone(), e))
+            .collect();
+
+        let nodes = |pairs: Vec<(String, i32)>| {
+            let mut out: Vec<SpaceWeatherNode> = pairs
+                .into_iter()
+                .map(|(id, depth)| match known.get(&id) {
+                    Some(e) => SpaceWeatherNode::from_event(e, depth),
+                    None => SpaceWeatherNode::unknown(&id, depth),
+                })
+                .collect();
+            out.sort_by(|a, b| a.start_time.cmp(&b.start_time).then_with(|| a.activity_id.cmp(&b.activity_id)));
+            out
+        };
+
+        Ok(SpaceWeatherChain {
+            event: SpaceWeatherNode::from_event(&root, 0),
+            causes: nodes(causes),
+            effects: nodes(effects),
+            edges: self.repo.edges(&ids).await?,
+        })
+    }
+
+    // Ожидаемые приходы CME к Земле из последнего снимка ленты CME.
+    // Уже пришедшие показываются ещё past_hours — удар и буря растянуты во времени
+    pub async fn cme_arrivals(&self, past_hours: i64) -> Result<CmeArrivals, ApiError> {
+        if !(0..=24 * 14).contains(&past_hours) {
+            return Err(ApiError::Validation("past_hours must be in [0, 336]".to_string()));
+        }
+        let now = Utc::now();
+        let since = now - Duration::hours(past_hours);
+
+        // Неразобранный снимок — не «ударов не ожидается»: ошибку отдаём, а не пустой список
+        let mut items = Vec::new();
+        if let Some(entry) = self.cache_repo.get_latest("cme").await? {
+            let cmes = match SpacePayload::parse("cme", &entry.payload) {
+                Ok((SpacePayload::Cme(cmes), _)) => cmes,
+                Ok(_) => Vec::new(),
+                Err(e) => {
+                    tracing::warn!("cme arrivals: snapshot {} unreadable: {}", entry.id, e);
+                    return Err(ApiError::Internal(format!("CME snapshot {} unreadable: {}", entry.id, e)));
+                }
+            };
+            items = cmes
+                .iter()
+                .filter_map(|c| c.earth_arrival())
+                .filter(|a| a.arrival_time >= since)
+                .collect();
+        }
+        items.sort_by(|a, b| a.arrival_time.cmp(&b.arrival_time).then_with(|| a.activity_id.cmp(&b.activity_id)));
+
+        Ok(CmeArrivals {
+            generated_at: now,
+            since,
+            items,
+        })
+    }
+}
@@ -0,0 +1,52 @@
+// Drag-based model: проверка на аналитическом случае и на решении уравнения DBM
+// для γ = 0.2e-7 км⁻¹ и w = 400 км/с
+use chrono::{DateTime, TimeZone, Utc};
+use rust_iss::domain::drag_based_arrival;
+
+fn t0() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap()
+}
+
+fn hours_to_arrival(speed: f64) -> f64 {
+    let arrival = drag_based_arrival(t0(), speed).unwrap_or_else(|| panic!("no arrival for {} km/s", speed));
+    (arrival - t0()).num_seconds() as f64 / 3600.0
+}
+
+fn assert_hours(actual: f64, expected: f64, what: &str) {
+    // Бисекция останавливается на минуте, плюс округление до секунды
+    assert!((actual - expected).abs() <= 2.0 / 60.0, "{}: {}h vs {}h", what, actual, expected);
+}
+
+#[test]
+fn speed_equal_to_wind_is_constant() {
+    // Без торможения фронт идёт с постоянной скоростью: (1 а.е. − 21.5 R☉) / 400 км/с
+    let expected = (149_597_870.7 - 21.5 * 695_700.0) / 400.0 / 3600.0;
+    assert_hours(hours_to_arrival(400.0), expected, "v = w");
+}
+
+#[test]
+fn fast_cme_decelerates() {
+    // Корень r(t) = 1 а.е. для v0 = 1000 км/с; без торможения было бы 37.4 ч
+    assert_hours(hours_to_arrival(1000.0), 52.42, "v = 1000 km/s");
+}
+
+#[test]
+fn slow_cme_is_accelerated_by_wind() {
+    // Медленнее ветра — разгоняется, но приходит позже выброса со скоростью ветра
+    let slow = hours_to_arrival(300.0);
+    assert_hours(slow, 114.36, "v = 300 km/s");
+    assert!(slow < (149_597_870.7 - 21.5 * 695_700.0) / 300.0 / 3600.0);
+}
+
+#[test]
+fn faster_arrives_earlier() {
+    let hours: Vec<f64> = [300.0, 400.0, 1000.0, 2000.0, 3000.0].iter().map(|v| hours_to_arrival(*v)).collect();
+    assert!(hours.windows(2).all(|w| w[0] > w[1]), "{:?}", hours);
+}
+
+#[test]
+fn invalid_speed_has_no_arrival() {
+    for v in [0.0, -500.0, f64::NAN, f64::INFINITY] {
+        assert!(drag_based_arrival(t0(), v).is_none(), "speed {}", v);
+    }
+}
@@ -0,0 +1,109 @@
+// Сверка локальных эфемерид с опубликованными значениями:
+// примеры из Meeus «Astronomical Algorithms» (2-е изд.) и таблицы восхода/захода USNO.
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use rust_iss::domain::ephemeris::*;
+
+fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+}
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn assert_close(actual: f64, expected: f64, tol: f64, what: &str) {
+    assert!(
+        (actual - expected).abs() <= tol,
+        "{}: {} vs {} (tol {})",
+        what,
+        actual,
+        expected,
+        tol
+    );
+}
+
+fn assert_minutes(actual: Option<DateTime<Utc>>, expected: DateTime<Utc>, tol: i64, what: &str) {
+    let actual = actual.unwrap_or_else(|| panic!("{}: missing", what));
+    let diff = (actual - expected).num_seconds().abs();
+    assert!(diff <= tol * 60, "{}: {} vs {}", what, actual, expected);
+}
+
+#[test]
+fn sun_position_meeus_25a() {
+    // 1992-10-13 0h TD: α = 13h13m31.4s, δ = −7°47′06″
+    let eq = sun_equatorial(utc(1992, 10, 13, 0, 0));
+    assert_close(eq.ra, 198.38083, 0.02, "sun ra");
+    assert_close(eq.dec, -7.78507, 0.02, "sun dec");
+}
+
+#[test]
+fn moon_position_meeus_47a() {
+    // 1992-04-12 0h TD: λ = 133.162655°, β = −3.229126°, Δ = 368409.7 км
+    let t = utc(1992, 4, 12, 0, 0);
+    let (ecl, dist) = moon_ecliptic(t);
+    assert_close(ecl.lon, 133.162655, 0.05, "moon lon");
+    assert_close(ecl.lat, -3.229126, 0.05, "moon lat");
+    assert_close(dist, 368409.7, 100.0, "moon distance");
+
+    let (eq, _) = moon_equatorial(t);
+    assert_close(eq.ra, 134.688470, 0.05, "moon ra");
+    assert_close(eq.dec, 13.768368, 0.05, "moon dec");
+}
+
+#[test]
+fn moon_illumination_meeus_48a() {
+    // Тот же момент: освещённая доля k = 0.6786
+    let phase = moon_phase(utc(1992, 4, 12, 0, 0));
+    assert_close(phase.illumination, 0.6786, 0.005, "illumination");
+    assert_eq!(phase.name, "waxing_gibbous");
+}
+
+#[test]
+fn moon_phase_at_published_lunations() {
+    // Полнолуние 2024-04-23 23:49 UTC и новолуние (полное затмение) 2024-04-08 18:21 UTC
+    let full = moon_phase(utc(2024, 4, 23, 23, 49));
+    assert!(full.illumination > 0.995, "{:?}", full);
+    assert_eq!(full.name, "full_moon");
+
+    let new = moon_phase(utc(2024, 4, 8, 18, 21));
+    assert!(new.illumination < 0.005, "{:?}", new);
+    assert_eq!(new.name, "new_moon");
+}
+
+#[test]
+fn sunrise_sunset_usno() {
+    // Лондон, 2021-06-21: восход 03:43 UTC, заход 20:21 UTC
+    let london = sun_rise_set(date(2021, 6, 21), 51.5074, -0.1278);
+    assert_minutes(london.rise, utc(2021, 6, 21, 3, 43), 2, "london rise");
+    assert_minutes(london.set, utc(2021, 6, 21, 20, 21), 2, "london set");
+
+    // Москва, 2024-06-21: восход 00:44 UTC, заход 18:18 UTC
+    let moscow = sun_rise_set(date(2024, 6, 21), 55.7558, 37.6176);
+    assert_minutes(moscow.rise, utc(2024, 6, 21, 0, 44), 2, "moscow rise");
+    assert_minutes(moscow.set, utc(2024, 6, 21, 18, 18), 2, "moscow set");
+}
+
+#[test]
+fn twilight_at_solstice() {
+    // В середине июня Солнце в Лондоне опускается лишь до ~−15°:
+    // навигационные сумерки есть, астрономической ночи нет
+    let d = date(2021, 6, 21);
+    let tw = twilights(d, 51.5074, -0.1278);
+    let sun = sun_rise_set(d, 51.5074, -0.1278);
+
+    assert!(tw.astronomical.dawn.is_none() && tw.astronomical.dusk.is_none());
+    let (nd, cd, rise) = (tw.nautical.dawn.unwrap(), tw.civil.dawn.unwrap(), sun.rise.unwrap());
+    assert!(nd < cd && cd < rise);
+    let (set, cu, nu) = (sun.set.unwrap(), tw.civil.dusk.unwrap(), tw.nautical.dusk.unwrap());
+    assert!(set < cu && cu < nu);
+}
+
+#[test]
+fn polar_day_and_night() {
+    // Тромсё: полярный день в июне и полярная ночь в декабре
+    let summer = sun_rise_set(date(2024, 6, 21), 69.6492, 18.9553);
+    assert!(summer.always_up && summer.rise.is_none() && summer.set.is_none());
+
+    let winter = sun_rise_set(date(2024, 12, 21), 69.6492, 18.9553);
+    assert!(winter.always_down && winter.rise.is_none() && winter.set.is_none());
+}
@@ -0,0 +1,54 @@
+// Разбор класса вспышки GOES и пиковый поток 1–8 Å
+use rust_iss::domain::FlareClass;
+
+fn assert_close(actual: f64, expected: f64, what: &str) {
+    assert!((actual / expected - 1.0).abs() <= 1e-9, "{}: {} vs {}", what, actual, expected);
+}
+
+#[test]
+fn parses_magnitude() {
+    let c = FlareClass::parse("M2.5").unwrap();
+    assert_eq!(c.letter, 'M');
+    assert_close(c.magnitude, 2.5, "magnitude");
+    assert_close(c.flux(), 2.5e-5, "M2.5 flux");
+    assert_eq!(c.label(), "M2.5");
+}
+
+#[test]
+fn x_class_is_open_ended() {
+    // X10 — это 10 × 10⁻⁴, а не следующая буква шкалы
+    let c = FlareClass::parse("X10").unwrap();
+    assert_close(c.flux(), 1e-3, "X10 flux");
+    assert_eq!(c.label(), "X10.0");
+    assert!(c.flux() > FlareClass::parse("X9.3").unwrap().flux());
+}
+
+#[test]
+fn lowercase_letter_and_padding() {
+    let c = FlareClass::parse("  c3.1 ").unwrap();
+    assert_eq!(c.letter, 'C');
+    assert_close(c.flux(), 3.1e-6, "c3.1 flux");
+}
+
+#[test]
+fn empty_magnitude_means_one() {
+    let c = FlareClass::parse("B").unwrap();
+    assert_close(c.magnitude, 1.0, "magnitude");
+    assert_close(c.flux(), 1e-7, "B flux");
+}
+
+#[test]
+fn classes_are_ordered_by_flux() {
+    let fluxes: Vec<f64> = ["A9.9", "B1.0", "C1.0", "M1.0", "X1.0"]
+        .iter()
+        .map(|s| FlareClass::parse(s).unwrap().flux())
+        .collect();
+    assert!(fluxes.windows(2).all(|w| w[0] < w[1]), "{:?}", fluxes);
+}
+
+#[test]
+fn rejects_garbage() {
+    for s in ["", "   ", "Z1.0", "M2.5x", "M-1", "M0", "Mnan", "1.5", "Ж2"] {
+        assert!(FlareClass::parse(s).is_none(), "{:?} should not parse", s);
+    }
+}